@@ -0,0 +1,130 @@
+use super::language::Language;
+
+// a line after toggling comment, the text after byte
+// `index` of the original line is shifted by `shift` bytes.
+pub struct ToggledLine {
+    pub content: String,
+    pub index: usize,
+    pub shift: isize,
+}
+
+// the leading spaces and tabs of the line, other whitespace
+// like no-break spaces is taken as text.
+fn indent_of(line: &str) -> &str {
+    let end = line.len() - line.trim_start_matches([' ', '\t']).len();
+    return &line[..end];
+}
+
+// comments the lines at their minimum indent with the line comment
+// token, or wraps each line with the block comment tokens if the
+// language has no line comment. uncomments the lines if all of them
+// are commented. blank lines are kept.
+// returns None if the language has no comment token.
+pub fn toggle_comments(lines: &[&str], language: Language) -> Option<Vec<ToggledLine>> {
+    let (start, end) = match (language.line_comment(), language.block_comment()) {
+        (Some(token), _) => (token, ""),
+        (None, Some(tokens)) => tokens,
+        (None, None) => return None,
+    };
+    let is_blank = |line: &str| line.trim().is_empty();
+    let is_commented = lines.iter().filter(|line| !is_blank(line)).all(|line| {
+        let text = line[indent_of(line).len()..].trim_end();
+        text.len() >= start.len() + end.len() && text.starts_with(start) && text.ends_with(end)
+    });
+    let indent_len = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| indent_of(line).len())
+        .min()
+        .unwrap_or(0);
+
+    let toggled = lines.iter().map(|line| {
+        if is_blank(line) {
+            return ToggledLine {
+                content: line.to_string(),
+                index: 0,
+                shift: 0,
+            };
+        }
+        if is_commented {
+            return uncomment(line, start, end);
+        }
+        let (indent, text) = line.split_at(indent_len);
+        let content = match end.is_empty() {
+            true => format!("{}{} {}", indent, start, text),
+            false => format!("{}{} {} {}", indent, start, text, end),
+        };
+        ToggledLine {
+            content,
+            index: indent_len,
+            shift: start.len() as isize + 1,
+        }
+    });
+    return Some(toggled.collect());
+}
+
+// removes the comment tokens and a space inside them
+fn uncomment(line: &str, start: &str, end: &str) -> ToggledLine {
+    let indent = indent_of(line);
+    let mut text = &line[indent.len()..];
+    if !end.is_empty() {
+        text = text.trim_end();
+        text = text[..text.len() - end.len()].trim_end_matches(' ');
+    }
+    let text = &text[start.len()..];
+    let uncommented = text.strip_prefix(' ').unwrap_or(text);
+    let removed_len = start.len() + text.len() - uncommented.len();
+    return ToggledLine {
+        content: format!("{}{}", indent, uncommented),
+        index: indent.len(),
+        shift: -(removed_len as isize),
+    };
+}
+
+#[test]
+fn toggle_comments_test() {
+    let toggle = |lines: &[&str], language| {
+        toggle_comments(lines, language)
+            .unwrap()
+            .into_iter()
+            .map(|line| line.content)
+            .collect::<Vec<String>>()
+    };
+
+    let lines = ["    if a {", "", "        b();", "    }"];
+    let commented = toggle(&lines, Language::Rust);
+    assert_eq!(
+        commented,
+        vec!["    // if a {", "", "    //     b();", "    // }"]
+    );
+    let commented: Vec<&str> = commented.iter().map(String::as_str).collect();
+    assert_eq!(toggle(&commented, Language::Rust), lines);
+
+    // partly commented lines are commented again
+    assert_eq!(toggle(&["#a", "b"], Language::Python), vec!["# #a", "# b"]);
+    assert_eq!(
+        toggle(&["  #a", "  # b"], Language::Shell),
+        vec!["  a", "  b"]
+    );
+    assert_eq!(toggle(&["select 1;"], Language::Sql), vec!["-- select 1;"]);
+    assert_eq!(toggle(&["<p>"], Language::Html), vec!["<!-- <p> -->"]);
+    assert_eq!(toggle(&["<!--<p>-->  "], Language::Html), vec!["<p>"]);
+    assert!(toggle_comments(&["a"], Language::Plain).is_none());
+
+    let line = &toggle_comments(&["  //  a"], Language::Go).unwrap()[0];
+    assert_eq!(
+        (line.content.as_str(), line.index, line.shift),
+        ("   a", 2, -3)
+    );
+
+    // no-break spaces are not indent
+    let lines = ["\u{a0}a", " b"];
+    let commented = toggle(&lines, Language::Rust);
+    assert_eq!(commented, vec!["// \u{a0}a", "//  b"]);
+    let commented: Vec<&str> = commented.iter().map(String::as_str).collect();
+    assert_eq!(toggle(&commented, Language::Rust), lines);
+    assert_eq!(
+        toggle(&["\u{a0}// a"], Language::Rust),
+        vec!["// \u{a0}// a"]
+    );
+}
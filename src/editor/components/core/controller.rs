@@ -1,9 +1,9 @@
 use std::io;
 
-use crossterm::{event::KeyCode, style::Stylize};
+use crossterm::event::KeyCode;
 
 use crate::{
-    editor::{direction::Direction, text_area::TextArea},
+    editor::{direction::Direction, text_area::TextArea, theme::Theme},
     utils::{Cursor, Terminal},
 };
 
@@ -30,16 +30,26 @@ impl LineComponentController {
 
         Cursor::move_to_row(render_pos)?;
         Cursor::move_to_col(0)?;
-        print!("{}", self.prompt.bold().black().on_white());
+        let theme = Theme::current();
+        print!("{}", theme.prompt.apply(self.prompt));
 
         Cursor::move_to_col(Terminal::width() - self.button.len())?;
-        print!("{}", self.button.bold().black().on_white());
+        print!("{}", theme.prompt.apply(self.button));
 
         self.text_area.move_cursor_to_end(false)?;
         self.text_area.render()?;
         return Ok(());
     }
 
+    pub fn set_prompt(&mut self, prompt: &'static str) {
+        self.prompt = prompt;
+        self.text_area.margin_left = prompt.len();
+    }
+    pub fn set_button(&mut self, button: &'static str) {
+        self.button = button;
+        self.text_area.margin_right = button.len();
+    }
+
     pub fn edit(&mut self, key: KeyCode) -> io::Result<()> {
         if !self.editable {
             return Ok(());
@@ -8,7 +8,7 @@ use std::io;
 
 use crossterm::event::KeyEvent;
 
-use crate::editor::text_area::TextArea;
+use crate::editor::{keymap::EditorAction, message::EditorMessage, text_area::TextArea};
 
 pub trait LineComponent {
     const PROMPT: &'static str;
@@ -28,4 +28,15 @@ pub trait LineComponent {
     }
     fn open(&mut self) -> io::Result<()>;
     fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()>;
+
+    // resolves the action of current component bound in keymap.
+    fn action_resolve(&mut self, _action: EditorAction) -> io::Result<()> {
+        return Ok(());
+    }
+
+    // returns the message to show in the dashboard
+    // for the last resolved key.
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        None
+    }
 }
@@ -1,35 +1,50 @@
-use std::io;
+use std::{io, path::Path};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::editor::text_area::TextArea;
+use crate::editor::{keymap::EditorInput, message::EditorMessage, text_area::TextArea};
 
 use super::core::{LineComponent, LineComponentController};
 
 pub struct FileOpener {
+    // the path checked to be an existing file
+    target: Option<String>,
+    message: Option<EditorMessage>,
     comp: LineComponentController,
 }
 
 impl FileOpener {
     pub fn new() -> Self {
         Self {
+            target: None,
+            message: None,
             comp: Self::init_controller(),
         }
     }
 
     #[inline]
-    pub fn get_file_path(&mut self) -> &str {
-        self.comp.text_area.content()
+    pub fn take_file_path(&mut self) -> Option<String> {
+        self.target.take()
     }
 
-    #[inline]
-    pub fn is_open_file_callback_key(key: KeyEvent) -> bool {
-        key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Enter
+    fn check_path(&mut self) {
+        let path_str = self.comp.text_area.content();
+        let path = Path::new(path_str);
+        self.message = if path_str.trim().is_empty() {
+            Some(EditorMessage::warning("Path is empty"))
+        } else if !path.exists() {
+            Some(EditorMessage::error(format!("File not found: {}", path_str)))
+        } else if path.is_dir() {
+            Some(EditorMessage::error(format!("{} is a directory", path_str)))
+        } else {
+            self.target = Some(path_str.to_owned());
+            None
+        };
     }
 
     #[inline]
-    pub fn set_path(&mut self, path: &str) {
-        self.comp.text_area.set_content(path);
+    pub fn is_open_file_callback_key(input: EditorInput) -> bool {
+        input.is_key(KeyCode::Enter)
     }
 }
 
@@ -45,11 +60,20 @@ impl LineComponent for FileOpener {
     }
 
     fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
-        if key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT {
-            if TextArea::is_editing_key(key.code) {
-                self.comp.edit(key.code)?;
-            }
+        if !(key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT) {
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Enter => self.check_path(),
+            k if TextArea::is_editing_key(k) => self.comp.edit(k)?,
+            _ => {}
         }
         return Ok(());
     }
+
+    #[inline]
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        self.message.take()
+    }
 }
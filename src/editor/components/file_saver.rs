@@ -1,17 +1,29 @@
 use std::{
     fs::{self, File},
-    io,
+    io, mem,
     path::Path,
 };
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::editor::text_area::TextArea;
+use crate::editor::{
+    config::{Charset, FileProperties, LineEnding},
+    keymap::EditorInput,
+    message::EditorMessage,
+    text_area::TextArea,
+};
 
 use super::core::{LineComponent, LineComponentController};
 
 pub struct FileSaver {
     editor_content: String,
+    // the rules of saving from `.editorconfig`,
+    // and the line ending joining `editor_content`
+    properties: FileProperties,
+    line_ending: LineEnding,
+    // whether the last saving succeeded
+    saved: bool,
+    message: Option<EditorMessage>,
     comp: LineComponentController,
 }
 
@@ -23,6 +35,10 @@ impl FileSaver {
         controller.text_area.set_content(Self::DEFAULT_FILE_NAME);
         return Self {
             editor_content: String::new(),
+            properties: FileProperties::default(),
+            line_ending: LineEnding::Lf,
+            saved: false,
+            message: None,
             comp: controller,
         };
     }
@@ -34,14 +50,45 @@ impl FileSaver {
         if !target_path.exists() {
             File::create(target_path)?;
         }
-        let bytes_to_write = self.editor_content.as_bytes();
+        let content = self
+            .properties
+            .apply_on_save(&self.editor_content, self.line_ending.as_str());
+        let charset = self.properties.charset.unwrap_or(Charset::Utf8);
+        let bytes_to_write = charset.encode(&content)?;
         fs::write(target_path_str, bytes_to_write)?;
         return Ok(());
     }
 
+    fn save_handler(&mut self) {
+        let path = self.path().to_owned();
+        if path.trim().is_empty() {
+            self.message = Some(EditorMessage::warning("Path is empty"));
+            return;
+        }
+        self.saved = match self.save() {
+            Ok(()) => {
+                self.message = Some(EditorMessage::info(format!("Saved to {}", path)));
+                true
+            }
+            Err(err) => {
+                self.message = Some(EditorMessage::error(format!(
+                    "Cannot save to {}: {}",
+                    path, err
+                )));
+                false
+            }
+        };
+    }
+
+    // returns whether the file is saved by the last Enter.
+    #[inline]
+    pub fn take_saved(&mut self) -> bool {
+        mem::take(&mut self.saved)
+    }
+
     #[inline]
-    pub fn is_save_callback_key(key: KeyEvent) -> bool {
-        key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Enter
+    pub fn is_save_callback_key(input: EditorInput) -> bool {
+        input.is_key(KeyCode::Enter)
     }
 
     #[inline]
@@ -49,6 +96,17 @@ impl FileSaver {
         self.editor_content = content;
     }
 
+    #[inline]
+    pub fn set_properties(&mut self, properties: FileProperties, line_ending: LineEnding) {
+        self.properties = properties;
+        self.line_ending = line_ending;
+    }
+
+    #[inline]
+    pub fn path(&self) -> &str {
+        self.comp.text_area.content()
+    }
+
     #[inline]
     pub fn set_path(&mut self, path: &str) {
         self.comp.text_area.set_content(path);
@@ -69,11 +127,16 @@ impl LineComponent for FileSaver {
     fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
         if key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT {
             match key.code {
-                KeyCode::Enter => self.save()?,
+                KeyCode::Enter => self.save_handler(),
                 k if TextArea::is_editing_key(k) => self.comp.edit(k)?,
                 _ => {}
             }
         }
         return Ok(());
     }
+
+    #[inline]
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        self.message.take()
+    }
 }
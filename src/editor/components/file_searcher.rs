@@ -0,0 +1,470 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    editor::{
+        keymap::{EditorAction, EditorInput},
+        message::EditorMessage,
+        pattern::SearchPattern,
+        project_search::{
+            apply_replacements, replace_preview, search_files, FileMatch, FileReplacement,
+            MAX_MATCH_COUNT,
+        },
+        text_area::TextArea,
+        theme::Theme,
+    },
+    utils::{Cursor, Terminal},
+};
+
+use super::core::{LineComponent, LineComponentController};
+
+#[derive(PartialEq)]
+enum FileSearcherState {
+    Searching,
+    Replacing,
+}
+
+// a row in the replacing preview
+enum PreviewRow {
+    File(usize),
+    Hunk(usize, usize),
+}
+
+pub struct FileSearcher {
+    state: FileSearcherState,
+
+    // the text used in last searching
+    searched_text: String,
+    use_regex: bool,
+    results: Vec<FileMatch>,
+
+    // the text used in last previewing
+    previewed_text: Option<String>,
+    preserve_case: bool,
+    preview: Vec<FileReplacement>,
+    preview_rows: Vec<PreviewRow>,
+
+    // index of selected row
+    selected: usize,
+    // count of rows scrolled out of the top
+    overflow_top: usize,
+
+    // the selected result to open
+    target: Option<FileMatch>,
+    // files changed by last replacing
+    changed_files: Vec<PathBuf>,
+    // message shown after replacing
+    status: Option<String>,
+    message: Option<EditorMessage>,
+
+    searcher: LineComponentController,
+    replacer: LineComponentController,
+}
+
+impl FileSearcher {
+    const REGEX_PROMPT: &'static str = "Regex in files: ";
+    const REPLACE_PROMPT: &'static str = "Replace with: ";
+    const REPLACE_CASE_PROMPT: &'static str = "Replace with (Aa): ";
+    const REPLACE_BUTTON: &'static str = "[Enter / Ctrl + X / S]";
+    // the first row to show results
+    const RESULT_START_ROW: usize = 2;
+
+    pub fn new() -> Self {
+        Self {
+            state: FileSearcherState::Searching,
+
+            searched_text: String::new(),
+            use_regex: false,
+            results: vec![],
+
+            previewed_text: None,
+            preserve_case: false,
+            preview: vec![],
+            preview_rows: vec![],
+
+            selected: 0,
+            overflow_top: 0,
+
+            target: None,
+            changed_files: vec![],
+            status: None,
+            message: None,
+
+            searcher: Self::init_controller(),
+            replacer: LineComponentController {
+                prompt: Self::REPLACE_PROMPT,
+                button: Self::REPLACE_BUTTON,
+                text_area: TextArea::new(Self::REPLACE_PROMPT.len(), Self::REPLACE_BUTTON.len()),
+                position: Self::POSITION,
+                editable: true,
+            },
+        }
+    }
+
+    #[inline]
+    pub fn is_open_file_callback_key(input: EditorInput) -> bool {
+        input.is_key(KeyCode::Enter)
+    }
+    #[inline]
+    pub fn is_write_callback_key(input: EditorInput) -> bool {
+        input.is_action(EditorAction::SearchFilesWrite)
+    }
+
+    #[inline]
+    pub fn take_target(&mut self) -> Option<FileMatch> {
+        self.target.take()
+    }
+    #[inline]
+    pub fn take_changed_files(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.changed_files)
+    }
+
+    #[inline]
+    fn result_area_height() -> usize {
+        // `3` here means the top and bottom border
+        // and the input line.
+        Terminal::height() - 3
+    }
+
+    #[inline]
+    fn current_controller(&mut self) -> &mut LineComponentController {
+        match self.state {
+            FileSearcherState::Searching => &mut self.searcher,
+            FileSearcherState::Replacing => &mut self.replacer,
+        }
+    }
+
+    #[inline]
+    fn row_count(&self) -> usize {
+        match self.state {
+            FileSearcherState::Searching => self.results.len(),
+            FileSearcherState::Replacing => self.preview_rows.len(),
+        }
+    }
+
+    fn search(&mut self) {
+        let text = self.searcher.text_area.content().to_owned();
+        let pattern = SearchPattern::for_replacing(&text, self.use_regex, self.preserve_case);
+        self.results = match pattern {
+            Some(pattern) => {
+                let (results, truncated) = search_files(Path::new("."), &pattern);
+                if truncated {
+                    let text = format!("{0}+ matches, only {0} listed", MAX_MATCH_COUNT);
+                    self.message = Some(EditorMessage::warning(text));
+                }
+                results
+            }
+            None => {
+                if !text.is_empty() {
+                    self.message = Some(EditorMessage::error(format!("Invalid pattern: {}", text)));
+                }
+                vec![]
+            }
+        };
+        self.searched_text = text;
+        self.selected = 0;
+        self.overflow_top = 0;
+        self.status = None;
+    }
+
+    fn build_preview(&mut self) {
+        let replacement = self.replacer.text_area.content().to_owned();
+        let pattern =
+            SearchPattern::for_replacing(&self.searched_text, self.use_regex, self.preserve_case);
+        self.preview = match pattern {
+            Some(pattern) => replace_preview(
+                Path::new("."),
+                &pattern,
+                &replacement,
+                self.preserve_case,
+            ),
+            None => vec![],
+        };
+        self.preview_rows = self
+            .preview
+            .iter()
+            .enumerate()
+            .flat_map(|(file_index, file)| {
+                let hunk_rows = (0..file.hunks.len()).map(move |i| PreviewRow::Hunk(file_index, i));
+                std::iter::once(PreviewRow::File(file_index)).chain(hunk_rows)
+            })
+            .collect();
+        self.previewed_text = Some(replacement);
+        self.selected = 0;
+        self.overflow_top = 0;
+    }
+
+    // includes or excludes the selected hunk,
+    // or all hunks in the selected file.
+    fn toggle_selected(&mut self) {
+        match self.preview_rows.get(self.selected) {
+            Some(PreviewRow::File(file_index)) => {
+                let file = &mut self.preview[*file_index];
+                let accepted = file.accepted_count() == 0;
+                file.hunks.iter_mut().for_each(|h| h.accepted = accepted);
+            }
+            Some(PreviewRow::Hunk(file_index, hunk_index)) => {
+                let hunk = &mut self.preview[*file_index].hunks[*hunk_index];
+                hunk.accepted = !hunk.accepted;
+            }
+            None => {}
+        }
+    }
+
+    fn write_replacements(&mut self) -> io::Result<()> {
+        let report = apply_replacements(Path::new("."), &self.preview);
+        let mut status = format!(
+            "Replaced {} occurrence(s) in {} file(s)",
+            report.occurrence_count, report.file_count
+        );
+        if !report.skipped.is_empty() {
+            status += &format!(", skipped {} file(s) changed on disk", report.skipped.len());
+        }
+        if !report.failed.is_empty() {
+            status += &format!(", failed to write {} file(s)", report.failed.len());
+        }
+        self.message = Some(match report.failed.first() {
+            Some((path, err)) => {
+                EditorMessage::error(format!("{} ({}: {})", status, path.display(), err))
+            }
+            None => EditorMessage::info(status.clone()),
+        });
+        self.changed_files = report.changed;
+        self.status = Some(status);
+
+        // back to searching
+        self.state = FileSearcherState::Searching;
+        self.searched_text.clear();
+        self.results.clear();
+        self.preview.clear();
+        self.preview_rows.clear();
+        self.previewed_text = None;
+        self.selected = 0;
+        self.overflow_top = 0;
+        self.searcher.open()?;
+        return Ok(());
+    }
+
+    fn move_selection(&mut self, key: KeyCode) {
+        let row_count = self.row_count();
+        if row_count == 0 {
+            return;
+        }
+        match key {
+            KeyCode::Up if self.selected > 0 => self.selected -= 1,
+            KeyCode::Down if self.selected < row_count - 1 => self.selected += 1,
+            _ => {}
+        }
+
+        // scroll to make the selected result visible
+        let area_height = Self::result_area_height();
+        if self.selected < self.overflow_top {
+            self.overflow_top = self.selected;
+        } else if self.selected >= self.overflow_top + area_height {
+            self.overflow_top = self.selected + 1 - area_height;
+        }
+    }
+
+    fn toggle_regex(&mut self) -> io::Result<()> {
+        if self.state != FileSearcherState::Searching {
+            return Ok(());
+        }
+        self.use_regex = !self.use_regex;
+        self.searched_text.clear();
+        let prompt = if self.use_regex {
+            Self::REGEX_PROMPT
+        } else {
+            Self::PROMPT
+        };
+        self.searcher.set_prompt(prompt);
+        self.searcher.open()
+    }
+
+    fn toggle_preserve_case(&mut self) -> io::Result<()> {
+        self.preserve_case = !self.preserve_case;
+        self.previewed_text = None;
+        let prompt = if self.preserve_case {
+            Self::REPLACE_CASE_PROMPT
+        } else {
+            Self::REPLACE_PROMPT
+        };
+        self.replacer.set_prompt(prompt);
+        self.open()
+    }
+
+    fn row_text(&self, index: usize) -> String {
+        match self.state {
+            FileSearcherState::Searching => format!(" {}", self.results[index]),
+            FileSearcherState::Replacing => {
+                let check_box = |accepted: bool| if accepted { "[x]" } else { "[ ]" };
+                match self.preview_rows[index] {
+                    PreviewRow::File(file_index) => {
+                        let file = &self.preview[file_index];
+                        format!(
+                            " {} {} ({}/{})",
+                            check_box(file.accepted_count() > 0),
+                            file.path.display(),
+                            file.accepted_count(),
+                            file.hunks.len()
+                        )
+                    }
+                    PreviewRow::Hunk(file_index, hunk_index) => {
+                        let hunk = &self.preview[file_index].hunks[hunk_index];
+                        format!(
+                            "     {} {}:{}: {}  =>  {}",
+                            check_box(hunk.accepted),
+                            hunk.pos.row,
+                            hunk.pos.col,
+                            hunk.before.trim(),
+                            hunk.after.trim()
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    fn render_results(&self) -> io::Result<()> {
+        let term_width = Terminal::width();
+        let saved_col = Cursor::pos_col()?;
+        let saved_row = Cursor::pos_row()?;
+
+        let visible_end = self
+            .row_count()
+            .min(self.overflow_top + Self::result_area_height());
+        let mut row = Self::RESULT_START_ROW;
+        for index in self.overflow_top..visible_end {
+            let mut line = self.row_text(index);
+            if line.len() > term_width {
+                let mut end = term_width;
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+                line.truncate(end);
+            }
+            let padding = " ".repeat(term_width - line.len());
+            let line = format!("{}{}", line, padding);
+
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            if index == self.selected {
+                print!("{}", Theme::current().selection.apply(line));
+            } else {
+                print!("{}", line);
+            }
+            row += 1;
+        }
+
+        // show the status or empty result
+        let message = match &self.status {
+            Some(status) if self.row_count() == 0 => Some(status.as_str()),
+            _ if self.row_count() == 0 && !self.searched_text.is_empty() => Some("No results"),
+            _ => None,
+        };
+        if let Some(message) = message {
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            Terminal::clear_after_cursor()?;
+            print!(" {}", Theme::current().hint.apply(message));
+            row += 1;
+        }
+
+        // clear the remaining rows
+        while row < Self::RESULT_START_ROW + Self::result_area_height() {
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            Terminal::clear_after_cursor()?;
+            row += 1;
+        }
+
+        Cursor::move_to_row(saved_row)?;
+        Cursor::move_to_col(saved_col)?;
+        return Ok(());
+    }
+}
+
+impl LineComponent for FileSearcher {
+    const PROMPT: &'static str = "Find in files: ";
+    const BUTTON: &'static str = "[Enter / Ctrl + R]";
+    const POSITION: isize = 1;
+    const EDITABLE: bool = true;
+
+    fn open(&mut self) -> io::Result<()> {
+        self.current_controller().open()?;
+        self.render_results()?;
+        return Ok(());
+    }
+
+    fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !(key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT) {
+            return Ok(());
+        }
+
+        match (&self.state, key.code) {
+            (FileSearcherState::Searching, KeyCode::Enter) => {
+                let is_searched = !self.searched_text.is_empty()
+                    && self.searcher.text_area.content() == self.searched_text;
+                if is_searched {
+                    self.target = self.results.get(self.selected).cloned();
+                } else {
+                    self.search();
+                    self.render_results()?;
+                }
+            }
+            (FileSearcherState::Replacing, KeyCode::Enter) => {
+                let replacement = self.replacer.text_area.content();
+                if self.previewed_text.as_deref() != Some(replacement) {
+                    self.build_preview();
+                    self.render_results()?;
+                }
+            }
+            (_, KeyCode::Up | KeyCode::Down) => {
+                self.move_selection(key.code);
+                self.render_results()?;
+            }
+            (_, k) if TextArea::is_editing_key(k) => self.current_controller().edit(k)?,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    fn action_resolve(&mut self, action: EditorAction) -> io::Result<()> {
+        match (&self.state, action) {
+            (_, EditorAction::SearchFilesRegex) => self.toggle_regex()?,
+            (_, EditorAction::SearchFilesPreserveCase) => self.toggle_preserve_case()?,
+            (FileSearcherState::Searching, EditorAction::SearchFilesReplace)
+                if !self.results.is_empty() =>
+            {
+                self.state = FileSearcherState::Replacing;
+                self.selected = 0;
+                self.overflow_top = 0;
+                self.open()?;
+            }
+            (FileSearcherState::Replacing, EditorAction::SearchFilesExclude) => {
+                self.toggle_selected();
+                self.render_results()?;
+            }
+            (FileSearcherState::Replacing, EditorAction::SearchFilesWrite) => {
+                // avoid writing the replacements not previewed
+                let replacement = self.replacer.text_area.content();
+                if self.previewed_text.as_deref() == Some(replacement) {
+                    self.write_replacements()?;
+                } else {
+                    self.build_preview();
+                }
+                self.render_results()?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    #[inline]
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        self.message.take()
+    }
+}
@@ -3,7 +3,11 @@ use std::io;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::{
-    editor::{cursor_pos::EditorCursorPos, text_area::TextArea},
+    editor::{
+        cursor_pos::EditorCursorPos,
+        keymap::{EditorAction, EditorInput},
+        text_area::TextArea,
+    },
     utils::LoopTraverser,
 };
 
@@ -65,12 +69,12 @@ impl Finder {
     // --- --- --- --- --- ---
 
     #[inline]
-    pub fn is_finding_key(key: KeyEvent) -> bool {
-        key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Enter
+    pub fn is_finding_key(input: EditorInput) -> bool {
+        input.is_action(EditorAction::FindNext)
     }
     #[inline]
-    pub fn is_reverse_finding_key(key: KeyEvent) -> bool {
-        key.modifiers == KeyModifiers::SHIFT && key.code == KeyCode::Enter
+    pub fn is_reverse_finding_key(input: EditorInput) -> bool {
+        input.is_action(EditorAction::FindPrevious)
     }
 }
 
@@ -109,16 +113,6 @@ impl LineComponent for Finder {
                     text_area.render()?;
                 }
             }
-            KeyCode::Enter => {
-                let current_target = self.content();
-                if let Some(last_appended) = self.history.last() {
-                    // avoid repetitive history content
-                    if current_target == last_appended {
-                        return Ok(());
-                    }
-                }
-                self.history.append(current_target.to_owned());
-            }
             k if TextArea::is_editing_key(k) => {
                 self.history.reset_index();
                 self.comp.edit(k)?;
@@ -127,4 +121,18 @@ impl LineComponent for Finder {
         }
         return Ok(());
     }
+
+    fn action_resolve(&mut self, action: EditorAction) -> io::Result<()> {
+        if action == EditorAction::FindNext {
+            let current_target = self.content();
+            if let Some(last_appended) = self.history.last() {
+                // avoid repetitive history content
+                if current_target == last_appended {
+                    return Ok(());
+                }
+            }
+            self.history.append(current_target.to_owned());
+        }
+        return Ok(());
+    }
 }
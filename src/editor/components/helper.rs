@@ -22,7 +22,7 @@ impl Helper {
         self.comp.render()
     }
 
-    pub fn key_resolve(&self, key: KeyEvent) -> io::Result<()> {
+    pub fn key_resolve(&self, _key: KeyEvent) -> io::Result<()> {
         return Ok(());
     }
 }
\ No newline at end of file
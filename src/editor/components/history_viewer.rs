@@ -0,0 +1,277 @@
+use std::{
+    io,
+    time::{Duration, SystemTime},
+};
+
+use crossterm::{
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    style::Stylize,
+};
+
+use crate::{
+    editor::{keymap::EditorInput, message::EditorMessage, text_area::TextArea, theme::Theme},
+    utils::{format_age, Cursor, Terminal},
+};
+
+use super::core::{LineComponent, LineComponentController};
+
+// a state in the undo tree to display.
+pub struct HistoryRow {
+    pub seq: usize,
+    // the indent level of the branch
+    pub depth: usize,
+    // the state this branch starts from,
+    // None if the row is not the first of a branch.
+    pub branch_from: Option<usize>,
+    pub summary: String,
+    pub time: SystemTime,
+    pub is_current: bool,
+}
+
+// the state to travel to.
+pub enum HistoryTarget {
+    State(usize),
+    Earlier(Duration),
+    Later(Duration),
+}
+
+impl HistoryTarget {
+    // parses commands like `12`, `earlier 5m` or `later 30s`.
+    fn parse(command: &str) -> Option<Self> {
+        let command = command.trim();
+        if let Ok(seq) = command.trim_start_matches('#').parse::<usize>() {
+            return Some(Self::State(seq));
+        }
+
+        let (direction, offset) = command.split_once(' ')?;
+        let offset = offset.trim();
+        let unit_index = offset.find(|ch: char| !ch.is_ascii_digit())?;
+        let count = offset[..unit_index].parse::<u64>().ok()?;
+        let seconds = match &offset[unit_index..] {
+            "s" => count,
+            "m" => count.checked_mul(60)?,
+            "h" => count.checked_mul(60 * 60)?,
+            "d" => count.checked_mul(60 * 60 * 24)?,
+            _ => return None,
+        };
+        let duration = Duration::from_secs(seconds);
+        match direction {
+            "earlier" => Some(Self::Earlier(duration)),
+            "later" => Some(Self::Later(duration)),
+            _ => None,
+        }
+    }
+}
+
+pub struct HistoryViewer {
+    rows: Vec<HistoryRow>,
+
+    // index of selected row
+    selected: usize,
+    // count of rows scrolled out of the top
+    overflow_top: usize,
+
+    // the state to travel to
+    target: Option<HistoryTarget>,
+    message: Option<EditorMessage>,
+
+    comp: LineComponentController,
+}
+
+impl HistoryViewer {
+    // the first row to show the tree
+    const TREE_START_ROW: usize = 2;
+
+    pub fn new() -> Self {
+        Self {
+            rows: vec![],
+
+            selected: 0,
+            overflow_top: 0,
+
+            target: None,
+            message: None,
+
+            comp: Self::init_controller(),
+        }
+    }
+
+    #[inline]
+    pub fn is_travel_key(input: EditorInput) -> bool {
+        input.is_key(KeyCode::Enter)
+    }
+
+    #[inline]
+    pub fn take_target(&mut self) -> Option<HistoryTarget> {
+        self.target.take()
+    }
+
+    // sets the states to display and selects current state.
+    pub fn set_rows(&mut self, rows: Vec<HistoryRow>) {
+        self.selected = rows.iter().position(|row| row.is_current).unwrap_or(0);
+        self.rows = rows;
+        self.overflow_top = 0;
+        self.comp.text_area.clear();
+        self.scroll_to_selected();
+    }
+
+    #[inline]
+    fn tree_area_height() -> usize {
+        // `3` here means the top and bottom border
+        // and the input line.
+        Terminal::height() - 3
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let area_height = Self::tree_area_height();
+        if self.selected < self.overflow_top {
+            self.overflow_top = self.selected;
+        } else if self.selected >= self.overflow_top + area_height {
+            self.overflow_top = self.selected + 1 - area_height;
+        }
+    }
+
+    fn move_selection(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up if self.selected > 0 => self.selected -= 1,
+            KeyCode::Down if self.selected + 1 < self.rows.len() => self.selected += 1,
+            _ => {}
+        }
+        self.scroll_to_selected();
+    }
+
+    fn row_text(&self, index: usize) -> String {
+        let row = &self.rows[index];
+        let marker = if row.is_current { "*" } else { "o" };
+        let branch = match row.branch_from {
+            Some(seq) => format!(" <- #{}", seq),
+            None => String::new(),
+        };
+        format!(
+            " {}{} #{} {} ({}){}",
+            "| ".repeat(row.depth),
+            marker,
+            row.seq,
+            row.summary,
+            format_age(row.time),
+            branch
+        )
+    }
+
+    fn render_tree(&self) -> io::Result<()> {
+        let term_width = Terminal::width();
+        let saved_col = Cursor::pos_col()?;
+        let saved_row = Cursor::pos_row()?;
+
+        let visible_end = self
+            .rows
+            .len()
+            .min(self.overflow_top + Self::tree_area_height());
+        let mut row = Self::TREE_START_ROW;
+        for index in self.overflow_top..visible_end {
+            let mut line = self.row_text(index);
+            if line.len() > term_width {
+                let mut end = term_width;
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+                line.truncate(end);
+            }
+            let padding = " ".repeat(term_width - line.len());
+            let line = format!("{}{}", line, padding);
+
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            if index == self.selected {
+                print!("{}", Theme::current().selection.apply(line));
+            } else if self.rows[index].is_current {
+                print!("{}", line.bold());
+            } else {
+                print!("{}", line);
+            }
+            row += 1;
+        }
+
+        // clear the remaining rows
+        while row < Self::TREE_START_ROW + Self::tree_area_height() {
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            Terminal::clear_after_cursor()?;
+            row += 1;
+        }
+
+        Cursor::move_to_row(saved_row)?;
+        Cursor::move_to_col(saved_col)?;
+        return Ok(());
+    }
+}
+
+impl LineComponent for HistoryViewer {
+    const PROMPT: &'static str = "Go to state: ";
+    const BUTTON: &'static str = "[Enter / Up / Down]";
+    const POSITION: isize = 1;
+    const EDITABLE: bool = true;
+
+    fn open(&mut self) -> io::Result<()> {
+        self.comp.open()?;
+        self.render_tree()?;
+        return Ok(());
+    }
+
+    fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !(key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT) {
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Enter => {
+                let command = self.comp.text_area.content();
+                if command.trim().is_empty() {
+                    self.target = self
+                        .rows
+                        .get(self.selected)
+                        .map(|row| HistoryTarget::State(row.seq));
+                    return Ok(());
+                }
+                self.target = HistoryTarget::parse(command);
+                if self.target.is_none() {
+                    self.message = Some(EditorMessage::warning(format!(
+                        "Invalid command: {}",
+                        command
+                    )));
+                }
+            }
+            KeyCode::Up | KeyCode::Down => {
+                self.move_selection(key.code);
+                self.render_tree()?;
+            }
+            k if TextArea::is_editing_key(k) => self.comp.edit(k)?,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    #[inline]
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        self.message.take()
+    }
+}
+
+#[test]
+fn history_target_parse_test() {
+    let parse = |command| match HistoryTarget::parse(command) {
+        Some(HistoryTarget::State(seq)) => format!("state {}", seq),
+        Some(HistoryTarget::Earlier(d)) => format!("earlier {}", d.as_secs()),
+        Some(HistoryTarget::Later(d)) => format!("later {}", d.as_secs()),
+        None => String::from("none"),
+    };
+    assert_eq!(parse("12"), "state 12");
+    assert_eq!(parse("#3"), "state 3");
+    assert_eq!(parse("earlier 5m"), "earlier 300");
+    assert_eq!(parse("later 30s"), "later 30");
+    assert_eq!(parse("later 2h"), "later 7200");
+    assert_eq!(parse("earlier 5"), "none");
+    assert_eq!(parse("sooner 5m"), "none");
+    assert_eq!(parse("earlier 300000000000000000d"), "none");
+    assert_eq!(parse("later 1d"), "later 86400");
+}
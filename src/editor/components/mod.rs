@@ -1,24 +1,30 @@
 mod core;
 mod file_saver;
 mod file_opener;
+mod file_searcher;
 mod finder;
+mod history_viewer;
 mod positioner;
 mod replacer;
+mod swap_recoverer;
 mod helper;
 
 pub use self::core::LineComponent;
 pub use file_saver::FileSaver;
 pub use file_opener::FileOpener;
+pub use file_searcher::FileSearcher;
 pub use finder::Finder;
+pub use history_viewer::{HistoryRow, HistoryTarget, HistoryViewer};
 pub use positioner::Positioner;
 pub use replacer::Replacer;
+pub use swap_recoverer::{RecoverAction, SwapRecoverer};
 pub use helper::Helper;
 
 use std::io;
 
 use crossterm::event::KeyEvent;
 
-use super::core::EditorState;
+use super::{core::EditorState, keymap::EditorAction, message::EditorMessage};
 
 pub struct EditorComponentManager {
     pub use_line_component: bool,
@@ -33,6 +39,9 @@ pub struct EditorComponentManager {
 
     // screen components
     pub helper: Helper,
+    pub file_searcher: FileSearcher,
+    pub history_viewer: HistoryViewer,
+    pub swap_recoverer: SwapRecoverer,
 }
 
 impl EditorComponentManager {
@@ -48,6 +57,9 @@ impl EditorComponentManager {
             replacer: Replacer::new(),
 
             helper: Helper::new(),
+            file_searcher: FileSearcher::new(),
+            history_viewer: HistoryViewer::new(),
+            swap_recoverer: SwapRecoverer::new(),
         }
     }
 
@@ -60,8 +72,47 @@ impl EditorComponentManager {
             EditorState::Replacing => self.replacer.key_resolve(key)?,
 
             EditorState::ReadingHelpMsg => self.helper.key_resolve(key)?,
+            EditorState::SearchingFiles => self.file_searcher.key_resolve(key)?,
+            EditorState::ViewingHistory => self.history_viewer.key_resolve(key)?,
+            EditorState::Recovering => self.swap_recoverer.key_resolve(key)?,
             _ => unreachable!(),
         }
         return Ok(());
     }
+
+    // whether the component in `current_state` takes typed text,
+    // the text is typed into the buffer when no component is used.
+    pub fn is_typing(&self, current_state: EditorState) -> bool {
+        match current_state {
+            EditorState::Replacing => self.replacer.is_typing(),
+            EditorState::ReadingHelpMsg | EditorState::Recovering => false,
+            _ => true,
+        }
+    }
+
+    pub fn resolve_action(
+        &mut self,
+        current_state: EditorState,
+        action: EditorAction,
+    ) -> io::Result<()> {
+        match current_state {
+            EditorState::Finding => self.finder.action_resolve(action)?,
+            EditorState::Replacing => self.replacer.action_resolve(action)?,
+            EditorState::SearchingFiles => self.file_searcher.action_resolve(action)?,
+            EditorState::Recovering => self.swap_recoverer.action_resolve(action)?,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    pub fn take_message(&mut self, current_state: EditorState) -> Option<EditorMessage> {
+        match current_state {
+            EditorState::Saving => self.file_saver.take_message(),
+            EditorState::Opening => self.file_opener.take_message(),
+            EditorState::Positioning => self.positioner.take_message(),
+            EditorState::SearchingFiles => self.file_searcher.take_message(),
+            EditorState::ViewingHistory => self.history_viewer.take_message(),
+            _ => None,
+        }
+    }
 }
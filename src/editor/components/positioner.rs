@@ -2,12 +2,15 @@ use std::io;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::editor::{cursor_pos::EditorCursorPos, text_area::TextArea};
+use crate::editor::{
+    cursor_pos::EditorCursorPos, keymap::EditorInput, message::EditorMessage, text_area::TextArea,
+};
 
 use super::{core::LineComponentController, LineComponent};
 
 pub struct Positioner {
     target: EditorCursorPos,
+    message: Option<EditorMessage>,
     comp: LineComponentController,
 }
 
@@ -20,6 +23,7 @@ impl Positioner {
             .set_placeholder(&initial_cursor_pos.short_display());
         return Self {
             target: initial_cursor_pos,
+            message: None,
             comp: controller,
         };
     }
@@ -32,8 +36,8 @@ impl Positioner {
     }
 
     #[inline]
-    pub fn is_positioning_key(key: KeyEvent) -> bool {
-        key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Enter
+    pub fn is_positioning_key(input: EditorInput) -> bool {
+        input.is_key(KeyCode::Enter)
     }
 
     #[inline]
@@ -60,12 +64,20 @@ impl LineComponent for Positioner {
 
         match key.code {
             KeyCode::Enter => {
-                let target_pos_str = self.comp.text_area.content();
-                let parsed_pos = EditorCursorPos::parse(target_pos_str);
+                let target_pos_str = self.comp.text_area.content().to_owned();
+                let parsed_pos = EditorCursorPos::parse(&target_pos_str);
                 self.comp.text_area.clear();
 
-                if let Some(pos) = parsed_pos {
-                    self.target = pos
+                match parsed_pos {
+                    Some(pos) => self.target = pos,
+                    // empty input jumps to the placeholder position
+                    None if target_pos_str.trim().is_empty() => {}
+                    None => {
+                        self.message = Some(EditorMessage::warning(format!(
+                            "Invalid position: {}",
+                            target_pos_str
+                        )));
+                    }
                 }
             }
             k if TextArea::is_editing_key(k) => self.comp.edit(k)?,
@@ -73,4 +85,9 @@ impl LineComponent for Positioner {
         }
         return Ok(());
     }
+
+    #[inline]
+    fn take_message(&mut self) -> Option<EditorMessage> {
+        self.message.take()
+    }
 }
@@ -2,9 +2,11 @@ use std::io;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::{
-    editor::{cursor_pos::EditorCursorPos, text_area::TextArea},
-    utils::LoopTraverser,
+use crate::editor::{
+    cursor_pos::EditorCursorPos,
+    keymap::{EditorAction, EditorInput},
+    pattern::SearchPattern,
+    text_area::TextArea,
 };
 
 use super::{
@@ -16,11 +18,22 @@ use super::{
 enum ReplacerState {
     Searching,
     Replacing,
+    // confirming each matching to replace
+    Confirming,
 }
 
 pub struct Replacer {
     state: ReplacerState,
-    match_list: LoopTraverser<EditorCursorPos>,
+    // the matching at cursor, matchings after it are
+    // searched from the buffer after each replacing.
+    current: Option<EditorCursorPos>,
+    // positions replaced in current replacing, used to undo
+    replaced_list: Vec<EditorCursorPos>,
+
+    // the pattern used in current replacing
+    pattern: Option<SearchPattern>,
+    use_regex: bool,
+    preserve_case: bool,
 
     search_history: ComponentHistory,
     replace_history: ComponentHistory,
@@ -30,8 +43,12 @@ pub struct Replacer {
 }
 
 impl Replacer {
+    const REGEX_PROMPT: &'static str = "Regex: ";
     const REPLACE_PROMPT: &'static str = "Replace: ";
-    const REPLACE_BUTTON: &'static str = "[Ctrl + S / N / A]";
+    const REPLACE_CASE_PROMPT: &'static str = "Replace (Aa): ";
+    const REPLACE_BUTTON: &'static str = "[Enter / Ctrl + S / N / A]";
+    const CONFIRM_PROMPT: &'static str = "Replace with: ";
+    const CONFIRM_BUTTON: &'static str = "[y / n / a / q / u]";
 
     pub fn new() -> Self {
         let mut searcher_controller = Self::init_controller();
@@ -51,7 +68,12 @@ impl Replacer {
 
         return Self {
             state: ReplacerState::Searching,
-            match_list: LoopTraverser::new(false),
+            current: None,
+            replaced_list: vec![],
+
+            pattern: None,
+            use_regex: false,
+            preserve_case: false,
 
             search_history: ComponentHistory::new(),
             replace_history: ComponentHistory::new(),
@@ -62,18 +84,26 @@ impl Replacer {
     }
 
     #[inline]
-    pub fn first(&self) -> Option<&EditorCursorPos> {
-        self.match_list.first()
+    pub fn current(&self) -> Option<EditorCursorPos> {
+        self.current
+    }
+    #[inline]
+    pub fn set_current(&mut self, pos: Option<EditorCursorPos>) {
+        self.current = pos;
     }
 
     #[inline]
-    pub fn current(&self) -> &EditorCursorPos {
-        self.match_list.current()
+    pub fn push_replaced(&mut self, pos: EditorCursorPos) {
+        self.replaced_list.push(pos);
+    }
+    #[inline]
+    pub fn pop_replaced(&mut self) -> Option<EditorCursorPos> {
+        self.replaced_list.pop()
     }
 
     #[inline]
-    pub fn next(&mut self) -> Option<&EditorCursorPos> {
-        self.match_list.next()
+    pub fn pattern(&self) -> Option<&SearchPattern> {
+        self.pattern.as_ref()
     }
 
     #[inline]
@@ -85,16 +115,44 @@ impl Replacer {
         self.replacer.text_area.content()
     }
 
+    #[inline]
+    pub fn search_pattern(&self) -> Option<SearchPattern> {
+        SearchPattern::for_replacing(self.search_text(), self.use_regex, self.preserve_case)
+    }
+
+    // returns the matched text at `start` of `text` and
+    // the replacement text for it.
+    pub fn expand_at(&self, text: &str, start: usize) -> Option<(String, String)> {
+        let pattern = self.pattern.as_ref()?;
+        pattern.expand_at(text, start, self.replace_text(), self.preserve_case)
+    }
+
     // when pressed `search_key` (Enter) and exists search result,
     // this handler will be called.
-    pub fn search_handler(&mut self, pos_list: Vec<EditorCursorPos>) -> io::Result<()> {
+    pub fn search_handler(
+        &mut self,
+        pattern: SearchPattern,
+        first_pos: EditorCursorPos,
+    ) -> io::Result<()> {
         self.search_history.append(self.search_text().to_owned());
-        self.match_list.set_content(pos_list);
+        self.pattern = Some(pattern);
+        self.current = Some(first_pos);
         self.replacer.open()?;
         self.state = ReplacerState::Replacing;
         return Ok(());
     }
 
+    // when pressed `confirm_key` (Enter) in replacing state,
+    // this handler will be called.
+    pub fn confirm_handler(&mut self) -> io::Result<()> {
+        self.replace_handler();
+        self.state = ReplacerState::Confirming;
+        self.replacer.editable = false;
+        self.replacer.set_button(Self::CONFIRM_BUTTON);
+        self.replacer.set_prompt(Self::CONFIRM_PROMPT);
+        self.replacer.open()
+    }
+
     // when pressed `replace_one_key` or `replace_all_key`,
     // this handler will be called.
     pub fn replace_handler(&mut self) {
@@ -112,34 +170,93 @@ impl Replacer {
         self.state = ReplacerState::Searching;
         self.searcher.text_area.clear();
         self.replacer.text_area.clear();
-        self.match_list.clear();
+        self.current = None;
+        self.replaced_list.clear();
+        self.pattern = None;
+
+        let replace_prompt = if self.preserve_case {
+            Self::REPLACE_CASE_PROMPT
+        } else {
+            Self::REPLACE_PROMPT
+        };
+        self.replacer.editable = true;
+        self.replacer.set_button(Self::REPLACE_BUTTON);
+        self.replacer.set_prompt(replace_prompt);
+    }
+
+    fn toggle_regex(&mut self) -> io::Result<()> {
+        if self.state != ReplacerState::Searching {
+            return Ok(());
+        }
+        self.use_regex = !self.use_regex;
+        let prompt = if self.use_regex {
+            Self::REGEX_PROMPT
+        } else {
+            Self::PROMPT
+        };
+        self.searcher.set_prompt(prompt);
+        self.searcher.open()
+    }
+
+    fn toggle_preserve_case(&mut self) -> io::Result<()> {
+        if self.state == ReplacerState::Confirming {
+            return Ok(());
+        }
+        self.preserve_case = !self.preserve_case;
+        // literal text is matched in a different case sensitivity
+        if self.pattern.is_some() {
+            self.pattern = self.search_pattern();
+        }
+        let prompt = if self.preserve_case {
+            Self::REPLACE_CASE_PROMPT
+        } else {
+            Self::REPLACE_PROMPT
+        };
+        self.replacer.set_prompt(prompt);
+        self.open()
     }
 
     // --- --- --- --- --- ---
 
+    // no text is typed when confirming replacements
+    #[inline]
+    pub fn is_typing(&self) -> bool {
+        self.state != ReplacerState::Confirming
+    }
+
+    #[inline]
+    pub fn is_search_key(&self, input: EditorInput) -> bool {
+        self.state == ReplacerState::Searching && input.is_key(KeyCode::Enter)
+    }
+    #[inline]
+    pub fn is_confirm_key(&self, input: EditorInput) -> bool {
+        self.state == ReplacerState::Replacing && input.is_key(KeyCode::Enter)
+    }
     #[inline]
-    pub fn is_search_key(&self, key: KeyEvent) -> bool {
-        self.state == ReplacerState::Searching
-            && key.modifiers == KeyModifiers::NONE
-            && key.code == KeyCode::Enter
+    pub fn is_next_key(&self, input: EditorInput) -> bool {
+        self.is_replacing_key(input, EditorAction::ReplaceNext)
     }
     #[inline]
-    pub fn is_next_key(&self, key: KeyEvent) -> bool {
-        self.state == ReplacerState::Replacing
-            && key.modifiers == KeyModifiers::CONTROL
-            && key.code == KeyCode::Char('n')
+    pub fn is_replace_one_key(&self, input: EditorInput) -> bool {
+        self.is_replacing_key(input, EditorAction::ReplaceOne)
     }
     #[inline]
-    pub fn is_replace_one_key(&self, key: KeyEvent) -> bool {
-        self.state == ReplacerState::Replacing
-            && key.modifiers == KeyModifiers::CONTROL
-            && key.code == KeyCode::Char('s')
+    pub fn is_replace_all_key(&self, input: EditorInput) -> bool {
+        self.is_replacing_key(input, EditorAction::ReplaceAll)
     }
     #[inline]
-    pub fn is_replace_all_key(&self, key: KeyEvent) -> bool {
-        self.state == ReplacerState::Replacing
-            && key.modifiers == KeyModifiers::CONTROL
-            && key.code == KeyCode::Char('a')
+    pub fn is_undo_key(&self, input: EditorInput) -> bool {
+        self.state == ReplacerState::Confirming && input.is_action(EditorAction::ReplaceUndo)
+    }
+    #[inline]
+    pub fn is_quit_key(&self, input: EditorInput) -> bool {
+        self.state == ReplacerState::Confirming && input.is_action(EditorAction::ReplaceQuit)
+    }
+
+    // `action` in replacing or confirming state
+    #[inline]
+    fn is_replacing_key(&self, input: EditorInput, action: EditorAction) -> bool {
+        self.state != ReplacerState::Searching && input.is_action(action)
     }
 }
 
@@ -152,12 +269,14 @@ impl LineComponent for Replacer {
     fn open(&mut self) -> io::Result<()> {
         match self.state {
             ReplacerState::Searching => &mut self.searcher,
-            ReplacerState::Replacing => &mut self.replacer,
+            ReplacerState::Replacing | ReplacerState::Confirming => &mut self.replacer,
         }
         .open()
     }
     fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
-        if !(key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT) {
+        if !(key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT)
+            || self.state == ReplacerState::Confirming
+        {
             return Ok(());
         }
 
@@ -167,9 +286,7 @@ impl LineComponent for Replacer {
                     ReplacerState::Searching => {
                         (self.search_text().to_owned(), &mut self.search_history)
                     }
-                    ReplacerState::Replacing => {
-                        (self.replace_text().to_owned(), &mut self.replace_history)
-                    }
+                    _ => (self.replace_text().to_owned(), &mut self.replace_history),
                 };
                 let history_content = match key.code {
                     KeyCode::Up => {
@@ -184,7 +301,7 @@ impl LineComponent for Replacer {
                 if let Some(str) = history_content {
                     let text_area = match self.state {
                         ReplacerState::Searching => &mut self.searcher.text_area,
-                        ReplacerState::Replacing => &mut self.replacer.text_area,
+                        _ => &mut self.replacer.text_area,
                     };
                     text_area.set_content(str);
                     text_area.move_cursor_to_end(false)?;
@@ -193,11 +310,20 @@ impl LineComponent for Replacer {
             }
             k if TextArea::is_editing_key(k) => match self.state {
                 ReplacerState::Searching => &mut self.searcher,
-                ReplacerState::Replacing => &mut self.replacer,
+                _ => &mut self.replacer,
             }
             .edit(key.code)?,
             _ => {}
         }
         return Ok(());
     }
+
+    fn action_resolve(&mut self, action: EditorAction) -> io::Result<()> {
+        match action {
+            EditorAction::ReplaceRegex => self.toggle_regex()?,
+            EditorAction::ReplacePreserveCase => self.toggle_preserve_case()?,
+            _ => {}
+        }
+        return Ok(());
+    }
 }
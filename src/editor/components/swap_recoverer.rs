@@ -0,0 +1,218 @@
+use std::io;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    editor::{
+        keymap::{EditorAction, EditorInput},
+        swap_file::SwapFile,
+        theme::Theme,
+    },
+    utils::{format_age, line_diff, Cursor, DiffLine, Terminal},
+};
+
+use super::core::{LineComponent, LineComponentController};
+
+pub enum RecoverAction {
+    Recover,
+    Delete,
+}
+
+pub struct SwapRecoverer {
+    swap: Option<SwapFile>,
+    // the content of the edited file on disk
+    file_content: String,
+
+    // is showing the difference between file and swap file
+    show_diff: bool,
+    // count of rows scrolled out of the top
+    overflow_top: usize,
+
+    action: Option<RecoverAction>,
+    comp: LineComponentController,
+}
+
+impl SwapRecoverer {
+    // the first row to show the information
+    const CONTENT_START_ROW: usize = 2;
+
+    pub fn new() -> Self {
+        Self {
+            swap: None,
+            file_content: String::new(),
+
+            show_diff: false,
+            overflow_top: 0,
+
+            action: None,
+            comp: Self::init_controller(),
+        }
+    }
+
+    #[inline]
+    pub fn is_action_key(input: EditorInput) -> bool {
+        input.is_action(EditorAction::RecoverRestore)
+            || input.is_action(EditorAction::RecoverDelete)
+    }
+
+    #[inline]
+    pub fn has_swap(&self) -> bool {
+        self.swap.is_some()
+    }
+
+    pub fn set_swap(&mut self, swap: Option<SwapFile>, file_content: String) {
+        self.swap = swap;
+        self.file_content = file_content;
+        self.show_diff = false;
+        self.overflow_top = 0;
+    }
+
+    // returns the action and the swap file it works on.
+    pub fn take_action(&mut self) -> Option<(RecoverAction, SwapFile)> {
+        let action = self.action.take()?;
+        return Some((action, self.swap.take()?));
+    }
+
+    #[inline]
+    fn content_area_height() -> usize {
+        // `3` here means the top and bottom border
+        // and the input line.
+        Terminal::height() - 3
+    }
+
+    // truncates the line to fit the terminal width
+    fn fit(mut line: String) -> String {
+        let term_width = Terminal::width();
+        if line.len() > term_width {
+            let mut end = term_width;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line.truncate(end);
+        }
+        return line;
+    }
+
+    fn content_rows(&self) -> Vec<String> {
+        let Some(swap) = &self.swap else {
+            return vec![];
+        };
+        if self.show_diff {
+            let Some(diff) = line_diff(&self.file_content, &swap.content) else {
+                return vec![
+                    " The file and the swap file differ in too many lines".to_owned(),
+                    " to be compared line by line.".to_owned(),
+                ];
+            };
+            return diff
+                .into_iter()
+                .map(|line| match line {
+                    DiffLine::Same(l) => Self::fit(format!("  {}", l)),
+                    DiffLine::Removed(l) => {
+                        let line = Self::fit(format!("- {}", l));
+                        Theme::current().diff_removed.apply(line).to_string()
+                    }
+                    DiffLine::Added(l) => {
+                        let line = Self::fit(format!("+ {}", l));
+                        Theme::current().diff_added.apply(line).to_string()
+                    }
+                })
+                .collect();
+        }
+
+        let mut rows = vec![
+            format!(" Swap file of {} is found,", swap.file_path.display()),
+            format!(" which is written {}.", format_age(swap.modified_time)),
+        ];
+        if swap.is_owner_running() {
+            rows.push(format!(
+                " The file may be being edited by process {}.",
+                swap.pid
+            ));
+        }
+        rows.extend([
+            String::new(),
+            " r   | Recover the unsaved text from swap file".to_owned(),
+            " d   | Show / Hide difference between the file and swap file".to_owned(),
+            " x   | Delete the swap file".to_owned(),
+            " Esc | Ignore the swap file".to_owned(),
+        ]);
+        return rows;
+    }
+
+    fn render_content(&self) -> io::Result<()> {
+        let saved_col = Cursor::pos_col()?;
+        let saved_row = Cursor::pos_row()?;
+
+        let rows = self.content_rows();
+        let visible_end = rows
+            .len()
+            .min(self.overflow_top + Self::content_area_height());
+        let mut row = Self::CONTENT_START_ROW;
+        for line in rows.iter().take(visible_end).skip(self.overflow_top) {
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            Terminal::clear_after_cursor()?;
+            print!("{}", line);
+            row += 1;
+        }
+
+        // clear the remaining rows
+        while row < Self::CONTENT_START_ROW + Self::content_area_height() {
+            Cursor::move_to_row(row)?;
+            Cursor::move_to_col(0)?;
+            Terminal::clear_after_cursor()?;
+            row += 1;
+        }
+
+        Cursor::move_to_row(saved_row)?;
+        Cursor::move_to_col(saved_col)?;
+        return Ok(());
+    }
+}
+
+impl LineComponent for SwapRecoverer {
+    const PROMPT: &'static str = "Swap file found";
+    const BUTTON: &'static str = "[r / d / x / Esc]";
+    const POSITION: isize = 1;
+    const EDITABLE: bool = false;
+
+    fn open(&mut self) -> io::Result<()> {
+        self.comp.open()?;
+        self.render_content()?;
+        return Ok(());
+    }
+
+    fn key_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
+        if key.modifiers != KeyModifiers::NONE {
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Up if self.overflow_top > 0 => {
+                self.overflow_top -= 1;
+                self.render_content()?;
+            }
+            KeyCode::Down if self.overflow_top + 1 < self.content_rows().len() => {
+                self.overflow_top += 1;
+                self.render_content()?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    fn action_resolve(&mut self, action: EditorAction) -> io::Result<()> {
+        match action {
+            EditorAction::RecoverRestore => self.action = Some(RecoverAction::Recover),
+            EditorAction::RecoverDelete => self.action = Some(RecoverAction::Delete),
+            EditorAction::RecoverDiff => {
+                self.show_diff = !self.show_diff;
+                self.overflow_top = 0;
+                self.render_content()?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,387 @@
+use std::{fs, io, path::Path};
+
+use crate::editor::indent::IndentStyle;
+use crate::utils::{absolute_path, glob_match};
+
+use super::LineEnding;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Charset {
+    Latin1,
+    Utf8,
+    Utf8Bom,
+    Utf16Be,
+    Utf16Le,
+}
+
+impl Charset {
+    const UTF8_BOM: &'static [u8] = b"\xef\xbb\xbf";
+
+    pub fn decode(&self, bytes: &[u8]) -> io::Result<String> {
+        let invalid = |name: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("stream did not contain valid {}", name),
+            )
+        };
+        let utf16 = |to_u16: fn([u8; 2]) -> u16| {
+            if !bytes.len().is_multiple_of(2) {
+                return Err(invalid("UTF-16"));
+            }
+            let units = bytes.chunks(2).map(|pair| to_u16([pair[0], pair[1]]));
+            let text: String = char::decode_utf16(units)
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid("UTF-16"))?;
+            // the BOM is not part of the text
+            return Ok(text.strip_prefix('\u{feff}').unwrap_or(&text).to_owned());
+        };
+        match self {
+            Self::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+            Self::Utf8 | Self::Utf8Bom => {
+                let bytes = bytes.strip_prefix(Self::UTF8_BOM).unwrap_or(bytes);
+                String::from_utf8(bytes.to_vec()).map_err(|_| invalid("UTF-8"))
+            }
+            Self::Utf16Be => utf16(u16::from_be_bytes),
+            Self::Utf16Le => utf16(u16::from_le_bytes),
+        }
+    }
+
+    pub fn encode(&self, text: &str) -> io::Result<Vec<u8>> {
+        let utf16 = |to_bytes: fn(u16) -> [u8; 2]| {
+            let mut bytes = to_bytes(0xfeff).to_vec();
+            for unit in text.encode_utf16() {
+                bytes.extend(to_bytes(unit));
+            }
+            return bytes;
+        };
+        match self {
+            Self::Latin1 => text
+                .chars()
+                .map(u8::try_from)
+                .collect::<Result<_, _>>()
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "text contains chars out of Latin-1",
+                    )
+                }),
+            Self::Utf8 => Ok(text.as_bytes().to_vec()),
+            Self::Utf8Bom => Ok([Self::UTF8_BOM, text.as_bytes()].concat()),
+            Self::Utf16Be => Ok(utf16(u16::to_be_bytes)),
+            Self::Utf16Le => Ok(utf16(u16::to_le_bytes)),
+        }
+    }
+}
+
+// the properties set by `.editorconfig` files for a file,
+// None if the property is not set or is `unset`.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct FileProperties {
+    pub soft_tabs: Option<bool>,
+    pub indent_size: Option<usize>,
+    pub tab_width: Option<usize>,
+    pub line_ending: Option<LineEnding>,
+    pub charset: Option<Charset>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+}
+
+impl FileProperties {
+    const FILE_NAME: &'static str = ".editorconfig";
+
+    // reads `.editorconfig` files from the directory of `path` up
+    // to the root or the file with `root = true`, nearer files
+    // take precedence. unreadable files are skipped.
+    pub fn find(path: &Path) -> Self {
+        let path = absolute_path(path);
+        let mut files = vec![];
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if let Ok(text) = fs::read_to_string(current.join(Self::FILE_NAME)) {
+                let is_root = is_root_file(&text);
+                files.push((current.to_path_buf(), text));
+                if is_root {
+                    break;
+                }
+            }
+            dir = current.parent();
+        }
+
+        let mut properties = Self::default();
+        for (dir, text) in files.iter().rev() {
+            if let Some(rel_path) = relative_path(&path, dir) {
+                properties.apply(text, &rel_path);
+            }
+        }
+        return properties;
+    }
+
+    // applies the sections in `text` matching `rel_path`,
+    // the path relative to the `.editorconfig` file.
+    pub fn apply(&mut self, text: &str, rel_path: &str) {
+        let mut is_matched = false;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                is_matched = section_matches(section, rel_path);
+                continue;
+            }
+            if let (true, Some((key, value))) = (is_matched, line.split_once('=')) {
+                let key = key.trim().to_ascii_lowercase();
+                self.set(&key, &value.trim().to_ascii_lowercase());
+            }
+        }
+    }
+
+    // unknown keys and invalid values are ignored
+    fn set(&mut self, key: &str, value: &str) {
+        let size = || value.parse::<usize>().ok().filter(|n| (1..=16).contains(n));
+        let boolean = || match value {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+        match key {
+            "indent_style" => {
+                self.soft_tabs = match value {
+                    "space" => Some(true),
+                    "tab" => Some(false),
+                    _ => None,
+                };
+            }
+            // `tab` means using `tab_width`
+            "indent_size" => self.indent_size = size(),
+            "tab_width" => self.tab_width = size(),
+            "end_of_line" => {
+                self.line_ending = match value {
+                    "lf" => Some(LineEnding::Lf),
+                    "crlf" => Some(LineEnding::Crlf),
+                    _ => None,
+                };
+            }
+            "charset" => {
+                self.charset = match value {
+                    "latin1" => Some(Charset::Latin1),
+                    "utf-8" => Some(Charset::Utf8),
+                    "utf-8-bom" => Some(Charset::Utf8Bom),
+                    "utf-16be" => Some(Charset::Utf16Be),
+                    "utf-16le" => Some(Charset::Utf16Le),
+                    _ => None,
+                };
+            }
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = boolean(),
+            "insert_final_newline" => self.insert_final_newline = boolean(),
+            _ => {}
+        }
+    }
+
+    // the columns between tab stops, `indent_size` if not set
+    #[inline]
+    pub fn tab_width(&self) -> Option<usize> {
+        self.tab_width.or(self.indent_size)
+    }
+
+    // the indent style if `indent_style` is set, `tab_width`
+    // is the width of spaces if no size is set.
+    pub fn indent_style(&self, tab_width: usize) -> Option<IndentStyle> {
+        match self.soft_tabs? {
+            true => Some(IndentStyle::Spaces(
+                self.indent_size.or(self.tab_width).unwrap_or(tab_width),
+            )),
+            false => Some(IndentStyle::Tabs),
+        }
+    }
+
+    // applies the rules of saving to `content` whose
+    // lines are joined by `line_break`.
+    pub fn apply_on_save(&self, content: &str, line_break: &str) -> String {
+        let mut content = match self.trim_trailing_whitespace {
+            Some(true) => content
+                .split(line_break)
+                .map(|line| line.trim_end_matches([' ', '\t']))
+                .collect::<Vec<_>>()
+                .join(line_break),
+            _ => content.to_owned(),
+        };
+        match self.insert_final_newline {
+            Some(true) if !content.is_empty() && !content.ends_with(line_break) => {
+                content += line_break;
+            }
+            Some(false) => {
+                // only the final newline, blank lines before it are kept
+                if let Some(stripped) = content.strip_suffix(line_break) {
+                    content.truncate(stripped.len());
+                }
+            }
+            _ => {}
+        }
+        return content;
+    }
+}
+
+// whether the preamble before sections has `root = true`
+fn is_root_file(text: &str) -> bool {
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("root") && value.trim().eq_ignore_ascii_case("true")
+            {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+
+// the path relative to `dir` separated by `/`
+fn relative_path(path: &Path, dir: &Path) -> Option<String> {
+    let rel_path = path.strip_prefix(dir).ok()?;
+    let parts: Vec<String> = rel_path
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().to_string())
+        .collect();
+    return Some(parts.join("/"));
+}
+
+// a glob without `/` matches the file name in any directory,
+// otherwise it matches the path relative to `.editorconfig`.
+fn section_matches(section: &str, rel_path: &str) -> bool {
+    return expand_braces(section).iter().any(|glob| {
+        let glob = match glob.contains('/') {
+            true => glob.trim_start_matches('/').to_owned(),
+            false => format!("**/{}", glob),
+        };
+        glob_match(glob.as_bytes(), rel_path.as_bytes())
+    });
+}
+
+// expands `{a,b}` in glob to a glob for each choice,
+// braces without comma are kept as they are.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some(open) = glob.find('{') else {
+        return vec![glob.to_owned()];
+    };
+    let mut depth = 0;
+    let mut close = None;
+    let mut commas = vec![];
+    for (i, ch) in glob[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(open + i),
+            _ => {}
+        }
+    }
+    let (Some(close), false) = (close, commas.is_empty()) else {
+        return vec![glob.to_owned()];
+    };
+
+    let (head, tail) = (&glob[..open], &glob[close + 1..]);
+    let mut bounds = vec![open];
+    bounds.extend(commas);
+    bounds.push(close);
+    let mut globs = vec![];
+    for pair in bounds.windows(2) {
+        let choice = &glob[pair[0] + 1..pair[1]];
+        globs.extend(expand_braces(&format!("{}{}{}", head, choice, tail)));
+    }
+    return globs;
+}
+
+#[test]
+fn editorconfig_test() {
+    let text = "\
+root = true
+
+[*]
+end_of_line = lf
+insert_final_newline = true
+indent_style = space
+indent_size = 4
+
+[*.{js,json}]
+indent_size = 2
+
+[Makefile]
+indent_style = tab
+
+; comment
+[docs/**.md]
+trim_trailing_whitespace = true
+indent_size = unset
+";
+    assert!(is_root_file(text));
+    let properties = |rel_path: &str| {
+        let mut properties = FileProperties::default();
+        properties.apply(text, rel_path);
+        properties
+    };
+
+    let rust = properties("src/main.rs");
+    assert_eq!(rust.line_ending, Some(LineEnding::Lf));
+    assert_eq!(rust.indent_style(8), Some(IndentStyle::Spaces(4)));
+    assert_eq!(rust.tab_width(), Some(4));
+    assert_eq!(
+        properties("web/app.json").indent_style(8),
+        Some(IndentStyle::Spaces(2))
+    );
+    assert_eq!(
+        properties("Makefile").indent_style(8),
+        Some(IndentStyle::Tabs)
+    );
+    let doc = properties("docs/guide/intro.md");
+    assert_eq!(doc.trim_trailing_whitespace, Some(true));
+    assert_eq!(doc.indent_style(8), Some(IndentStyle::Spaces(8)));
+    assert_eq!(properties("README.md").trim_trailing_whitespace, None);
+
+    assert_eq!(expand_braces("*.{c,{h,hpp}}"), vec!["*.c", "*.h", "*.hpp"]);
+    assert_eq!(expand_braces("{single}"), vec!["{single}"]);
+
+    let saving = FileProperties {
+        trim_trailing_whitespace: Some(true),
+        insert_final_newline: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        saving.apply_on_save("a  \r\n\tb\t\r\n", "\r\n"),
+        "a\r\n\tb\r\n"
+    );
+    assert_eq!(saving.apply_on_save("a \nb", "\n"), "a\nb\n");
+    let saving = FileProperties {
+        insert_final_newline: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(saving.apply_on_save("a\n\n", "\n"), "a\n");
+    assert_eq!(saving.apply_on_save("a\r\n", "\r\n"), "a");
+}
+
+#[test]
+fn charset_test() {
+    let text = "añ\u{1f600}";
+    for charset in [
+        Charset::Utf8,
+        Charset::Utf8Bom,
+        Charset::Utf16Be,
+        Charset::Utf16Le,
+    ] {
+        let bytes = charset.encode(text).unwrap();
+        assert_eq!(charset.decode(&bytes).unwrap(), text);
+    }
+    assert_eq!(&Charset::Utf8Bom.encode("a").unwrap(), b"\xef\xbb\xbfa");
+    assert_eq!(&Charset::Utf16Le.encode("a").unwrap(), b"\xff\xfea\x00");
+    assert_eq!(Charset::Latin1.encode("añ").unwrap(), vec![b'a', 0xf1]);
+    assert_eq!(Charset::Latin1.decode(&[b'a', 0xf1]).unwrap(), "añ");
+    assert!(Charset::Latin1.encode("\u{1f600}").is_err());
+    assert!(Charset::Utf16Be.decode(b"\x00").is_err());
+}
@@ -0,0 +1,291 @@
+mod editorconfig;
+pub(super) mod toml;
+
+pub use editorconfig::{Charset, FileProperties};
+pub use toml::{ConfigError, ConfigValue};
+
+use std::{fs, io, time::Duration};
+
+use crossterm::style::Color;
+
+use crate::utils::config_dir;
+
+use super::{
+    keymap::{EditorAction, KeyChord, Keymap},
+    theme::parse_color,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+}
+
+// per-user settings loaded from
+// `$XDG_CONFIG_HOME/rusditor/config.toml`.
+pub struct EditorConfig {
+    // the name of a theme in `$XDG_CONFIG_HOME/rusditor/themes`
+    // or the path to a theme file
+    pub theme: String,
+    // overrides the accent color of the theme
+    pub accent_color: Option<Color>,
+    // the width of an indent level
+    pub tab_width: usize,
+    // insert spaces instead of `\t` for Tab key
+    pub soft_tabs: bool,
+    // keep and adjust the indent for new lines and brackets
+    pub auto_indent: bool,
+    // insert closers for typed brackets and quotes
+    pub auto_pairs: bool,
+    // the line ending used to save files
+    pub line_ending: LineEnding,
+    // write swap files for modified buffers
+    pub autosave: bool,
+    pub autosave_interval: Duration,
+    // the max count of states kept in the undo tree
+    pub history_size: usize,
+    // key sequences bound to actions, set in `[keys]` section
+    pub keymap: Keymap,
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            theme: String::from("default"),
+            accent_color: None,
+            tab_width: 4,
+            soft_tabs: true,
+            auto_indent: true,
+            auto_pairs: true,
+            line_ending: LineEnding::Crlf,
+            autosave: true,
+            autosave_interval: Duration::from_secs(2),
+            history_size: 1000,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl EditorConfig {
+    const FILE_NAME: &'static str = "config.toml";
+
+    // loads the config file, a missing file gives the default
+    // settings, invalid entries are skipped and returned.
+    pub fn load() -> (Self, Vec<ConfigError>) {
+        let Some(path) = config_dir().map(|dir| dir.join(Self::FILE_NAME)) else {
+            return (Self::default(), vec![]);
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (Self::default(), vec![]),
+            Err(err) => {
+                let error = ConfigError {
+                    file: String::from(Self::FILE_NAME),
+                    line: None,
+                    message: format!("cannot read {}: {}", path.display(), err),
+                };
+                (Self::default(), vec![error])
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let mut config = Self::default();
+        let (entries, mut errors) = toml::parse(text, Self::FILE_NAME);
+        for entry in entries {
+            if let Err(message) = config.set(&entry.key, &entry.value) {
+                errors.push(ConfigError {
+                    file: String::from(Self::FILE_NAME),
+                    line: Some(entry.line),
+                    message,
+                });
+            }
+        }
+        errors.sort_by_key(|err| err.line);
+        return (config, errors);
+    }
+
+    // sets an option from command line like `tab_width=2`,
+    // unquoted text is taken as a string.
+    pub fn set_from_arg(&mut self, arg: &str) -> Result<(), String> {
+        let Some((key, value)) = arg.split_once('=') else {
+            return Err(format!("expected `KEY=VALUE`, found `{}`", arg));
+        };
+        let (entries, errors) = toml::parse(&format!("{} = {}", key, value), "");
+        let value = match entries.into_iter().next() {
+            Some(entry) if errors.is_empty() => entry.value,
+            _ => ConfigValue::String(value.trim().to_owned()),
+        };
+        return self.set(key.trim(), &value);
+    }
+
+    // sets the option named `key`, returns the reason
+    // if the option or the value is invalid.
+    pub fn set(&mut self, key: &str, value: &ConfigValue) -> Result<(), String> {
+        let expected = |type_name: &str| {
+            format!(
+                "`{}` expects {}, found {}",
+                key,
+                type_name,
+                value.type_name()
+            )
+        };
+        let integer_in = |min: i64, max: i64| match value {
+            ConfigValue::Integer(n) if (min..=max).contains(n) => Ok(*n),
+            ConfigValue::Integer(n) => Err(format!(
+                "`{}` must be between {} and {}, found {}",
+                key, min, max, n
+            )),
+            _ => Err(expected("an integer")),
+        };
+        let boolean = || match value {
+            ConfigValue::Boolean(b) => Ok(*b),
+            _ => Err(expected("a boolean")),
+        };
+        let string = || match value {
+            ConfigValue::String(s) => Ok(s.as_str()),
+            _ => Err(expected("a string")),
+        };
+
+        match key {
+            "theme" => self.theme = string()?.to_owned(),
+            "accent_color" => {
+                let color = parse_color(value).map_err(|err| format!("`{}`: {}", key, err))?;
+                self.accent_color = Some(color);
+            }
+            "tab_width" => self.tab_width = integer_in(1, 16)? as usize,
+            "soft_tabs" => self.soft_tabs = boolean()?,
+            "auto_indent" => self.auto_indent = boolean()?,
+            "auto_pairs" => self.auto_pairs = boolean()?,
+            "line_ending" => {
+                self.line_ending = match string()? {
+                    "lf" => LineEnding::Lf,
+                    "crlf" => LineEnding::Crlf,
+                    other => {
+                        return Err(format!(
+                            "unknown line ending `{}`, expected `lf` or `crlf`",
+                            other
+                        ))
+                    }
+                };
+            }
+            "autosave" => self.autosave = boolean()?,
+            "autosave_interval" => {
+                self.autosave_interval = Duration::from_secs(integer_in(1, 3600)? as u64);
+            }
+            "history_size" => self.history_size = integer_in(1, 100_000)? as usize,
+            _ => match key.strip_prefix("keys.") {
+                Some(name) => self.set_keys(name, value)?,
+                None => return Err(format!("unknown option `{}`", key)),
+            },
+        }
+        return Ok(());
+    }
+
+    // binds key sequences like `"ctrl+k ctrl+c"` or a list of them
+    // to the action, an empty list unbinds the action.
+    fn set_keys(&mut self, name: &str, value: &ConfigValue) -> Result<(), String> {
+        let Some(action) = EditorAction::from_name(name) else {
+            return Err(format!("unknown action `{}`", name));
+        };
+        let values = match value {
+            ConfigValue::Array(items) => items.iter().collect(),
+            _ => vec![value],
+        };
+
+        let mut sequences = vec![];
+        for value in values {
+            let ConfigValue::String(keys) = value else {
+                return Err(format!(
+                    "`keys.{}` expects key sequences, found {}",
+                    name,
+                    value.type_name()
+                ));
+            };
+            if keys.trim().is_empty() {
+                continue;
+            }
+            let Some(sequence) = KeyChord::parse_sequence(keys) else {
+                return Err(format!("invalid key sequence `{}` for `{}`", keys, name));
+            };
+            if let Some((bound_sequence, bound)) = self.keymap.find_conflict(action, &sequence) {
+                let bound_keys: Vec<String> =
+                    bound_sequence.iter().map(|k| k.to_string()).collect();
+                return Err(format!(
+                    "key sequence `{}` for `{}` conflicts with `{}` bound to `{}`",
+                    keys,
+                    name,
+                    bound_keys.join(", "),
+                    bound.name()
+                ));
+            }
+            sequences.push(sequence);
+        }
+        self.keymap.bind(action, sequences);
+        return Ok(());
+    }
+}
+
+#[test]
+fn config_set_test() {
+    let text = "\
+accent_color = \"dark_blue\"
+tab_width = 2
+soft_tabs = \"yes\"
+line_ending = \"lf\"
+history_size = 0
+colour = \"red\"
+";
+    let (config, errors) = EditorConfig::parse(text);
+    assert_eq!(config.tab_width, 2);
+    assert!(config.soft_tabs);
+    assert_eq!(config.line_ending, LineEnding::Lf);
+    assert_eq!(config.history_size, 1000);
+
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "config.toml:3: `soft_tabs` expects a boolean, found a string",
+            "config.toml:5: `history_size` must be between 1 and 100000, found 0",
+            "config.toml:6: unknown option `colour`",
+        ]
+    );
+
+    let mut config = EditorConfig::default();
+    assert!(config.set_from_arg("tab_width=2").is_ok());
+    assert!(config.set_from_arg("line_ending=lf").is_ok());
+    assert!(config.set_from_arg("soft_tabs = false").is_ok());
+    assert_eq!(config.tab_width, 2);
+    assert_eq!(config.line_ending, LineEnding::Lf);
+    assert!(!config.soft_tabs);
+    assert!(config.set_from_arg("tab_width").is_err());
+    assert!(config.set_from_arg("tab_width=wide").is_err());
+    assert!(config.set_from_arg("accent_color=#005f87").is_ok());
+    assert!(config.set_from_arg("accent_color=27").is_ok());
+    assert!(config.set_from_arg("accent_color=teal").is_err());
+}
+
+#[test]
+fn config_keys_conflict_test() {
+    let text = "\
+[keys]
+undo = \"ctrl+k\"
+redo = [\"ctrl+y\", \"ctrl+k ctrl+c\"]
+";
+    let (_, errors) = EditorConfig::parse(text);
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec!["config.toml:3: key sequence `ctrl+k ctrl+c` for `redo` conflicts with `Ctrl + K` bound to `undo`"]
+    );
+}
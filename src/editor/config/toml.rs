@@ -0,0 +1,182 @@
+use std::fmt;
+
+use ::toml::de::{DeTable, DeValue};
+
+// a value of the config file, tables are flattened
+// into dotted keys and floats or datetimes are not used.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConfigValue {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "a string",
+            Self::Integer(_) => "an integer",
+            Self::Boolean(_) => "a boolean",
+            Self::Array(_) => "an array",
+        }
+    }
+}
+
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{:?}", s),
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::Boolean(b) => write!(f, "{}", b),
+            Self::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+// `key = value` in the config file, keys in a
+// `[section]` are prefixed with `section.`.
+#[derive(Debug)]
+pub struct ConfigEntry {
+    pub line: usize,
+    pub key: String,
+    pub value: ConfigValue,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    // the name of the file with the error
+    pub file: String,
+    // the line number in the config file,
+    // None if the error is about the whole file.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+// --- --- --- --- --- ---
+
+// 1-based line number of the byte `offset` in `text`.
+fn line_of(text: &str, offset: usize) -> usize {
+    return text[..offset.min(text.len())].matches('\n').count() + 1;
+}
+
+fn convert_value(value: &DeValue) -> Result<ConfigValue, String> {
+    let value = match value {
+        DeValue::String(s) => ConfigValue::String(s.to_string()),
+        DeValue::Integer(n) => match i64::from_str_radix(n.as_str(), n.radix()) {
+            Ok(n) => ConfigValue::Integer(n),
+            Err(_) => return Err(format!("integer `{}` is out of range", n.as_str())),
+        },
+        DeValue::Boolean(b) => ConfigValue::Boolean(*b),
+        DeValue::Array(items) => {
+            let items = items.iter().map(|item| convert_value(item.get_ref()));
+            ConfigValue::Array(items.collect::<Result<_, _>>()?)
+        }
+        DeValue::Float(_) | DeValue::Datetime(_) | DeValue::Table(_) => {
+            return Err(format!("{} values are not supported", value.type_str()));
+        }
+    };
+    return Ok(value);
+}
+
+// flattens `table` into entries keyed by dotted paths,
+// values that cannot be converted are reported.
+fn flatten(
+    text: &str,
+    file_name: &str,
+    prefix: &str,
+    table: &DeTable,
+    entries: &mut Vec<ConfigEntry>,
+    errors: &mut Vec<ConfigError>,
+) {
+    for (key, value) in table {
+        let full_key = match prefix.is_empty() {
+            true => key.get_ref().to_string(),
+            false => format!("{}.{}", prefix, key.get_ref()),
+        };
+        let line = line_of(text, key.span().start);
+        if let DeValue::Table(table) = value.get_ref() {
+            flatten(text, file_name, &full_key, table, entries, errors);
+            continue;
+        }
+        match convert_value(value.get_ref()) {
+            Ok(value) => entries.push(ConfigEntry {
+                line,
+                key: full_key,
+                value,
+            }),
+            Err(message) => errors.push(ConfigError {
+                file: file_name.to_owned(),
+                line: Some(line),
+                message: format!("`{}`: {}", full_key, message),
+            }),
+        }
+    }
+}
+
+// parses the whole file named `file_name` as TOML,
+// syntax errors are reported and the rest is kept.
+pub fn parse(text: &str, file_name: &str) -> (Vec<ConfigEntry>, Vec<ConfigError>) {
+    let (document, syntax_errors) = DeTable::parse_recoverable(text);
+    let mut entries = vec![];
+    let mut errors: Vec<ConfigError> = syntax_errors
+        .iter()
+        .map(|err| ConfigError {
+            file: file_name.to_owned(),
+            line: err.span().map(|span| line_of(text, span.start)),
+            message: err.message().trim_end().to_owned(),
+        })
+        .collect();
+    let table = document.get_ref();
+    flatten(text, file_name, "", table, &mut entries, &mut errors);
+    entries.sort_by_key(|entry| entry.line);
+    return (entries, errors);
+}
+
+#[test]
+fn config_parse_test() {
+    let text = "\
+# comment
+tab_width = 4 # trailing comment
+accent_color = \"dark_blue\"
+tab_width = 2
+
+[keys]
+save = [
+    'ctrl+s',
+    \"ctrl+x ctrl+s\",
+]
+\"find.next\" = true
+find = { prev = \"ctrl+p\" }
+ratio = 0.5
+";
+    let (entries, errors) = parse(text, "config.toml");
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|e| format!("{} {} = {}", e.line, e.key, e.value))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            "2 tab_width = 4",
+            "3 accent_color = \"dark_blue\"",
+            "7 keys.save = [\"ctrl+s\", \"ctrl+x ctrl+s\"]",
+            "11 keys.find.next = true",
+            "12 keys.find.prev = \"ctrl+p\"",
+        ]
+    );
+    let errors: Vec<Option<usize>> = errors.iter().map(|e| e.line).collect();
+    assert_eq!(errors, vec![Some(4), Some(13)]);
+}
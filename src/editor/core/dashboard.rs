@@ -1,15 +1,19 @@
-use std::io;
-
-use crossterm::style::Stylize;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use super::EditorState;
-use crate::editor::core::color::EditorColor;
 use crate::editor::cursor_pos::{EditorCursorPos, TerminalCursorPos};
+use crate::editor::indent::IndentStyle;
+use crate::editor::message::{EditorMessage, MessageLevel, MESSAGE_DURATION};
+use crate::editor::theme::Theme;
 use crate::utils::{Cursor, Terminal};
 
 pub struct EditorDashboard {
     cursor_pos: EditorCursorPos,
     state: EditorState,
+    indent_style: IndentStyle,
 
     // this cursor position is used to temporarily
     // save and restore cursor.
@@ -18,6 +22,9 @@ pub struct EditorDashboard {
     // this state is used to cache current state when
     // component state is set.
     saved_state: EditorState,
+
+    // the message shown in the divider and the time it is set
+    message: Option<(EditorMessage, Instant)>,
 }
 
 impl EditorDashboard {
@@ -25,9 +32,12 @@ impl EditorDashboard {
         Self {
             cursor_pos: EditorCursorPos { row: 1, col: 1 },
             state: EditorState::Saved,
+            indent_style: IndentStyle::Spaces(4),
 
             temp_cursor_pos: TerminalCursorPos { row: 1, col: 1 },
             saved_state: EditorState::Saved,
+
+            message: None,
         }
     }
 
@@ -39,16 +49,38 @@ impl EditorDashboard {
         Cursor::move_to_col(0)?;
 
         let state_str = format!(" {} ", self.state);
+        let indent_str = format!(" {} ", self.indent_style);
         let cursor_pos_str = format!(" {} ", self.cursor_pos);
 
         // `2` here is space for left-margin and right-margin
-        let remain_space = Terminal::width() - state_str.len() - cursor_pos_str.len();
-        let divider_str = " ".repeat(remain_space).on_white();
+        let remain_space = Terminal::width()
+            .saturating_sub(state_str.len() + indent_str.len() + cursor_pos_str.len());
+        let theme = Theme::current();
+        let divider_str = match &self.message {
+            Some((message, _)) => {
+                let mut text = format!(" {}", message.text);
+                if text.len() > remain_space {
+                    let mut end = remain_space;
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    text.truncate(end);
+                }
+                let text = format!("{:<width$}", text, width = remain_space);
+                match message.level {
+                    MessageLevel::Info => theme.message_info.apply(text),
+                    MessageLevel::Warning => theme.message_warning.apply(text),
+                    MessageLevel::Error => theme.message_error.apply(text),
+                }
+            }
+            None => theme.dashboard.apply(" ".repeat(remain_space)),
+        };
 
         print!(
-            "{}{divider_str}{}",
-            EditorColor::highlight_style(state_str),
-            EditorColor::highlight_style(cursor_pos_str)
+            "{}{divider_str}{}{}",
+            theme.dashboard_state.apply(state_str),
+            theme.dashboard.apply(indent_str),
+            theme.dashboard_state.apply(cursor_pos_str)
         );
         self.temp_cursor_pos.restore_pos()?;
         return Ok(());
@@ -58,6 +90,11 @@ impl EditorDashboard {
     pub fn state(&self) -> EditorState {
         self.state
     }
+    // returns the state cached when component state is set
+    #[inline]
+    pub fn saved_state(&self) -> EditorState {
+        self.saved_state
+    }
 
     pub fn set_state(&mut self, new_state: EditorState) -> io::Result<()> {
         if new_state.is_component_state() {
@@ -83,4 +120,42 @@ impl EditorDashboard {
         self.render()?;
         return Ok(());
     }
+
+    // the indent style is rendered with the next state or
+    // cursor position change
+    #[inline]
+    pub fn set_indent_style(&mut self, style: IndentStyle) {
+        self.indent_style = style;
+    }
+
+    // --- --- --- --- --- ---
+
+    pub fn set_message(&mut self, message: EditorMessage) -> io::Result<()> {
+        self.message = Some((message, Instant::now()));
+        self.render()?;
+        return Ok(());
+    }
+
+    pub fn clear_message(&mut self) -> io::Result<()> {
+        if self.message.take().is_some() {
+            self.render()?;
+        }
+        return Ok(());
+    }
+
+    // returns the time before current message expires.
+    pub fn message_remaining(&self) -> Option<Duration> {
+        let (_, set_time) = self.message.as_ref()?;
+        return Some(MESSAGE_DURATION.saturating_sub(set_time.elapsed()));
+    }
+
+    // clears the message shown longer than `MESSAGE_DURATION`,
+    // returns whether the message is cleared.
+    pub fn expire_message(&mut self) -> io::Result<bool> {
+        if self.message_remaining() != Some(Duration::ZERO) {
+            return Ok(false);
+        }
+        self.clear_message()?;
+        return Ok(true);
+    }
 }
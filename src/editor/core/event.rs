@@ -1,14 +1,37 @@
+use std::fmt;
+
 use crate::editor::cursor_pos::EditorCursorPos;
+use crate::editor::direction::Direction;
 
 #[derive(Debug, Clone)]
 pub enum EditorOperation {
     InsertChar(char),
     DeleteChar(char),
+    // merged from continuous `InsertChar` and `DeleteChar`,
+    // text in `DeleteText` is in the order of content.
+    InsertText(String),
+    DeleteText(String),
     InsertLine,
     DeleteLine,
+    // a pair of brackets or quotes with cursor in between
+    InsertPair(char, char),
+    DeletePair(char, char),
 
     //      from  , to
     Replace(String, String),
+
+    // the texts inserted at or removed from the start of
+    // lines, the first text is of the line at the row.
+    IndentLines(usize, Vec<String>),
+    OutdentLines(usize, Vec<String>),
+    // whole lines inserted before or removed from the row
+    InsertLines(usize, Vec<String>),
+    RemoveLines(usize, Vec<String>),
+    // the count of lines from the row moved up or down by one line
+    MoveLines(usize, usize, Direction),
+
+    // events grouped by a transaction of history
+    Group(Vec<EditorEvent>),
 }
 
 impl EditorOperation {
@@ -16,10 +39,62 @@ impl EditorOperation {
         match self {
             Self::InsertChar(ch) => Self::DeleteChar(*ch),
             Self::DeleteChar(ch) => Self::InsertChar(*ch),
+            Self::InsertText(text) => Self::DeleteText(text.clone()),
+            Self::DeleteText(text) => Self::InsertText(text.clone()),
             Self::InsertLine => Self::DeleteLine,
             Self::DeleteLine => Self::InsertLine,
+            Self::InsertPair(open, close) => Self::DeletePair(*open, *close),
+            Self::DeletePair(open, close) => Self::InsertPair(*open, *close),
 
             Self::Replace(from, to) => Self::Replace(to.clone(), from.clone()),
+            Self::IndentLines(row, texts) => Self::OutdentLines(*row, texts.clone()),
+            Self::OutdentLines(row, texts) => Self::IndentLines(*row, texts.clone()),
+            Self::InsertLines(row, texts) => Self::RemoveLines(*row, texts.clone()),
+            Self::RemoveLines(row, texts) => Self::InsertLines(*row, texts.clone()),
+            Self::MoveLines(row, count, dir) => {
+                let moved_row = match dir {
+                    Direction::Up => row - 1,
+                    _ => row + 1,
+                };
+                Self::MoveLines(moved_row, *count, dir.rev())
+            }
+
+            Self::Group(events) => Self::Group(
+                events
+                    .iter()
+                    .rev()
+                    .map(|ev| EditorEvent {
+                        op: ev.op.rev(),
+                        pos_before: ev.pos_after,
+                        pos_after: ev.pos_before,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for EditorOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsertChar(ch) => write!(f, "insert {:?}", ch.to_string()),
+            Self::DeleteChar(ch) => write!(f, "delete {:?}", ch.to_string()),
+            Self::InsertText(text) => write!(f, "insert {:?}", text),
+            Self::DeleteText(text) => write!(f, "delete {:?}", text),
+            Self::InsertLine => write!(f, "insert line"),
+            Self::DeleteLine => write!(f, "delete line"),
+            Self::InsertPair(open, close) => write!(f, "insert \"{}{}\"", open, close),
+            Self::DeletePair(open, close) => write!(f, "delete \"{}{}\"", open, close),
+
+            Self::Replace(from, to) => write!(f, "replace {:?} with {:?}", from, to),
+            Self::IndentLines(_, texts) => write!(f, "indent {} lines", texts.len()),
+            Self::OutdentLines(_, texts) => write!(f, "outdent {} lines", texts.len()),
+            Self::InsertLines(_, texts) => write!(f, "insert {} lines", texts.len()),
+            Self::RemoveLines(_, texts) => write!(f, "remove {} lines", texts.len()),
+            Self::MoveLines(_, count, Direction::Up) => write!(f, "move {} lines up", count),
+            Self::MoveLines(_, count, _) => write!(f, "move {} lines down", count),
+
+            Self::Group(events) => write!(f, "{} changes", events.len()),
         }
     }
 }
@@ -33,3 +108,299 @@ pub struct EditorEvent {
     pub pos_before: EditorCursorPos,
     pub pos_after: EditorCursorPos,
 }
+
+impl EditorEvent {
+    // merges the typing or deleting event `ev` into current event
+    // when `ev` continues at the position current event ends.
+    // whitespace after a word starts a new event.
+    // returns true if merged.
+    pub fn merge(&mut self, ev: &EditorEvent) -> bool {
+        if self.pos_after != ev.pos_before {
+            return false;
+        }
+
+        let (text, is_inserting) = match &self.op {
+            EditorOperation::InsertChar(ch) => (ch.to_string(), true),
+            EditorOperation::InsertText(text) => (text.clone(), true),
+            EditorOperation::DeleteChar(ch) => (ch.to_string(), false),
+            EditorOperation::DeleteText(text) => (text.clone(), false),
+            _ => return false,
+        };
+        let (ch, is_ev_inserting) = match ev.op {
+            EditorOperation::InsertChar(ch) => (ch, true),
+            EditorOperation::DeleteChar(ch) => (ch, false),
+            _ => return false,
+        };
+        if is_inserting != is_ev_inserting {
+            return false;
+        }
+
+        // the char adjacent to `ch`
+        let edge_ch = if is_inserting {
+            text.chars().last()
+        } else {
+            text.chars().next()
+        };
+        if ch.is_whitespace() && !edge_ch.is_some_and(|c| c.is_whitespace()) {
+            return false;
+        }
+
+        self.op = if is_inserting {
+            EditorOperation::InsertText(text + &ch.to_string())
+        } else {
+            EditorOperation::DeleteText(ch.to_string() + &text)
+        };
+        self.pos_after = ev.pos_after;
+        return true;
+    }
+}
+
+// --- --- --- --- --- ---
+
+// serialization used by the persistent history,
+// an event is encoded into tokens without whitespace.
+impl EditorEvent {
+    pub fn encode(&self, tokens: &mut Vec<String>) {
+        for num in [
+            self.pos_before.row,
+            self.pos_before.col,
+            self.pos_after.row,
+            self.pos_after.col,
+        ] {
+            tokens.push(num.to_string());
+        }
+
+        match &self.op {
+            EditorOperation::InsertChar(ch) => {
+                tokens.push("ic".to_owned());
+                tokens.push(encode_str(&ch.to_string()));
+            }
+            EditorOperation::DeleteChar(ch) => {
+                tokens.push("dc".to_owned());
+                tokens.push(encode_str(&ch.to_string()));
+            }
+            EditorOperation::InsertText(text) => {
+                tokens.push("it".to_owned());
+                tokens.push(encode_str(text));
+            }
+            EditorOperation::DeleteText(text) => {
+                tokens.push("dt".to_owned());
+                tokens.push(encode_str(text));
+            }
+            EditorOperation::InsertLine => tokens.push("il".to_owned()),
+            EditorOperation::DeleteLine => tokens.push("dl".to_owned()),
+            EditorOperation::InsertPair(open, close) | EditorOperation::DeletePair(open, close) => {
+                let name = match &self.op {
+                    EditorOperation::InsertPair(..) => "ip",
+                    _ => "dp",
+                };
+                tokens.push(name.to_owned());
+                tokens.push(encode_str(&format!("{}{}", open, close)));
+            }
+
+            EditorOperation::Replace(from, to) => {
+                tokens.push("r".to_owned());
+                tokens.push(encode_str(from));
+                tokens.push(encode_str(to));
+            }
+            EditorOperation::IndentLines(row, texts)
+            | EditorOperation::OutdentLines(row, texts)
+            | EditorOperation::InsertLines(row, texts)
+            | EditorOperation::RemoveLines(row, texts) => {
+                let name = match &self.op {
+                    EditorOperation::IndentLines(..) => "id",
+                    EditorOperation::OutdentLines(..) => "od",
+                    EditorOperation::InsertLines(..) => "al",
+                    _ => "rl",
+                };
+                tokens.push(name.to_owned());
+                tokens.push(row.to_string());
+                tokens.push(texts.len().to_string());
+                for text in texts {
+                    tokens.push(encode_str(text));
+                }
+            }
+            EditorOperation::MoveLines(row, count, dir) => {
+                tokens.push("ml".to_owned());
+                tokens.push(row.to_string());
+                tokens.push(count.to_string());
+                let dir = match dir {
+                    Direction::Up => "u",
+                    _ => "d",
+                };
+                tokens.push(dir.to_owned());
+            }
+
+            EditorOperation::Group(events) => {
+                tokens.push("g".to_owned());
+                tokens.push(events.len().to_string());
+                for ev in events {
+                    ev.encode(tokens);
+                }
+            }
+        }
+    }
+
+    pub fn decode<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        let mut next_num = || tokens.next()?.parse::<usize>().ok();
+        let pos_before = EditorCursorPos {
+            row: next_num()?,
+            col: next_num()?,
+        };
+        let pos_after = EditorCursorPos {
+            row: next_num()?,
+            col: next_num()?,
+        };
+
+        let next_str = |tokens: &mut dyn Iterator<Item = &'a str>| decode_str(tokens.next()?);
+        let op = match tokens.next()? {
+            "ic" => EditorOperation::InsertChar(next_str(tokens)?.chars().next()?),
+            "dc" => EditorOperation::DeleteChar(next_str(tokens)?.chars().next()?),
+            "it" => EditorOperation::InsertText(next_str(tokens)?),
+            "dt" => EditorOperation::DeleteText(next_str(tokens)?),
+            "il" => EditorOperation::InsertLine,
+            "dl" => EditorOperation::DeleteLine,
+            name @ ("ip" | "dp") => {
+                let pair = next_str(tokens)?;
+                let mut chars = pair.chars();
+                let (open, close) = (chars.next()?, chars.next()?);
+                match name {
+                    "ip" => EditorOperation::InsertPair(open, close),
+                    _ => EditorOperation::DeletePair(open, close),
+                }
+            }
+
+            "r" => EditorOperation::Replace(next_str(tokens)?, next_str(tokens)?),
+            name @ ("id" | "od" | "al" | "rl") => {
+                let row = tokens.next()?.parse::<usize>().ok()?;
+                let count = tokens.next()?.parse::<usize>().ok()?;
+                let mut texts = vec![];
+                for _ in 0..count {
+                    texts.push(next_str(tokens)?);
+                }
+                match name {
+                    "id" => EditorOperation::IndentLines(row, texts),
+                    "od" => EditorOperation::OutdentLines(row, texts),
+                    "al" => EditorOperation::InsertLines(row, texts),
+                    _ => EditorOperation::RemoveLines(row, texts),
+                }
+            }
+            "ml" => {
+                let row = tokens.next()?.parse::<usize>().ok()?;
+                let count = tokens.next()?.parse::<usize>().ok()?;
+                let dir = match tokens.next()? {
+                    "u" => Direction::Up,
+                    "d" => Direction::Down,
+                    _ => return None,
+                };
+                EditorOperation::MoveLines(row, count, dir)
+            }
+
+            "g" => {
+                let count = tokens.next()?.parse::<usize>().ok()?;
+                let mut events = vec![];
+                for _ in 0..count {
+                    events.push(Self::decode(tokens)?);
+                }
+                EditorOperation::Group(events)
+            }
+            _ => return None,
+        };
+        return Some(Self {
+            op,
+            pos_before,
+            pos_after,
+        });
+    }
+}
+
+// strings are prefixed with `"` so that empty
+// strings are still a token.
+fn encode_str(str: &str) -> String {
+    let mut result = String::from("\"");
+    for ch in str.chars() {
+        match ch {
+            '\\' => result.push_str("\\\\"),
+            ' ' => result.push_str("\\s"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch => result.push(ch),
+        }
+    }
+    return result;
+}
+
+fn decode_str(token: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = token.strip_prefix('"')?.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        result.push(match chars.next()? {
+            '\\' => '\\',
+            's' => ' ',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            _ => return None,
+        });
+    }
+    return Some(result);
+}
+
+#[test]
+fn event_encode_test() {
+    let pos = |row, col| EditorCursorPos { row, col };
+    let ev = EditorEvent {
+        op: EditorOperation::Group(vec![
+            EditorEvent {
+                op: EditorOperation::Replace("a b\n".to_owned(), String::new()),
+                pos_before: pos(2, 3),
+                pos_after: pos(2, 3),
+            },
+            EditorEvent {
+                op: EditorOperation::InsertChar('\\'),
+                pos_before: pos(1, 1),
+                pos_after: pos(1, 2),
+            },
+            EditorEvent {
+                op: EditorOperation::OutdentLines(3, vec!["\t".to_owned(), String::new()]),
+                pos_before: pos(3, 2),
+                pos_after: pos(3, 1),
+            },
+            EditorEvent {
+                op: EditorOperation::InsertPair('(', ')'),
+                pos_before: pos(4, 4),
+                pos_after: pos(4, 5),
+            },
+            EditorEvent {
+                op: EditorOperation::RemoveLines(5, vec!["a".to_owned()]),
+                pos_before: pos(5, 1),
+                pos_after: pos(5, 1),
+            },
+            EditorEvent {
+                op: EditorOperation::MoveLines(2, 3, Direction::Up),
+                pos_before: pos(2, 1),
+                pos_after: pos(1, 1),
+            },
+        ]),
+        pos_before: pos(1, 1),
+        pos_after: pos(1, 1),
+    };
+
+    let mut tokens = vec![];
+    ev.encode(&mut tokens);
+    let line = tokens.join(" ");
+    assert_eq!(
+        line,
+        "1 1 1 1 g 6 2 3 2 3 r \"a\\sb\\n \" 1 1 1 2 ic \"\\\\ 3 2 3 1 od 3 2 \"\\t \" 4 4 4 5 ip \"() 5 1 5 1 rl 5 1 \"a 2 1 1 1 ml 2 3 u"
+    );
+
+    let decoded = EditorEvent::decode(&mut line.split(' ')).unwrap();
+    let mut decoded_tokens = vec![];
+    decoded.encode(&mut decoded_tokens);
+    assert_eq!(decoded_tokens, tokens);
+}
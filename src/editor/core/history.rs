@@ -1,67 +1,566 @@
-use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use super::event::EditorEvent;
+use crate::editor::{components::HistoryRow, cursor_pos::EditorCursorPos};
 
+use super::event::{EditorEvent, EditorOperation};
+
+#[derive(Debug)]
+struct HistoryNode {
+    // the sequence number of the state, also used
+    // to locate the state by users.
+    seq: usize,
+    // the event from the parent state to this state,
+    // None for the root state.
+    event: Option<EditorEvent>,
+    // the time the state is reached by editing
+    time: SystemTime,
+
+    parent: usize,
+    children: Vec<usize>,
+    // the child to go into when redoing
+    active_child: Option<usize>,
+}
+
+// a step of travelling in the undo tree.
+pub enum HistoryStep<'a> {
+    Undo(&'a EditorEvent),
+    Redo(&'a EditorEvent),
+}
+
+// an undo tree, every editing after undoing
+// creates a new branch instead of dropping
+// the undone events.
 #[derive(Debug)]
 pub struct EditorHistory {
-    events: VecDeque<EditorEvent>,
+    // states sorted by sequence number,
+    // the first one is the root.
+    nodes: Vec<HistoryNode>,
+    // index of current state in `nodes`
+    current: usize,
+    // the sequence number for next state
+    next_seq: usize,
 
-    // events that is undone
-    undo_events: Vec<EditorEvent>,
-    // events that is redone
-    redo_events: Vec<EditorEvent>,
+    // the time of last appending, used to split
+    // continuous typing by time gap.
+    // None if undo or redo happened after appending.
+    last_append_time: Option<Instant>,
+
+    // the cursor position before the transaction and
+    // the events appended in the transaction.
+    transaction: Option<(EditorCursorPos, Vec<EditorEvent>)>,
+    // the count of nested `begin`
+    transaction_depth: usize,
+
+    // the max count of states to keep
+    max_size: usize,
 }
 
 impl EditorHistory {
-    const MAX_CACHED_EVENT: usize = 255;
+    const DEFAULT_MAX_SIZE: usize = 1000;
+    // the max time gap of typing to merge into one event
+    const MERGE_INTERVAL: Duration = Duration::from_secs(1);
 
     pub fn new() -> Self {
         Self {
-            events: VecDeque::<EditorEvent>::new(),
-            undo_events: Vec::<EditorEvent>::new(),
-            redo_events: Vec::<EditorEvent>::new(),
+            nodes: vec![HistoryNode {
+                seq: 0,
+                event: None,
+                time: SystemTime::now(),
+                parent: 0,
+                children: vec![],
+                active_child: None,
+            }],
+            current: 0,
+            next_seq: 1,
+
+            last_append_time: None,
+
+            transaction: None,
+            transaction_depth: 0,
+
+            max_size: Self::DEFAULT_MAX_SIZE,
         }
     }
 
-    #[inline]
-    // returns the last appended event
-    pub fn previous_event(&self) -> Option<&EditorEvent> {
-        self.events.back()
+    // sets the max count of states and removes the oldest ones.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        while self.nodes.len() > self.max_size {
+            let count = self.nodes.len();
+            self.prune();
+            if self.nodes.len() == count {
+                break;
+            }
+        }
     }
 
     pub fn undo(&mut self) -> Option<&EditorEvent> {
-        let option_op = if !self.redo_events.is_empty() {
-            self.redo_events.pop()
+        if self.current == 0 {
+            return None;
+        }
+        self.last_append_time = None;
+
+        let undone = self.current;
+        self.current = self.nodes[undone].parent;
+        self.nodes[self.current].active_child = Some(undone);
+        self.nodes[undone].event.as_ref()
+    }
+
+    pub fn redo(&mut self) -> Option<&EditorEvent> {
+        let child = self.nodes[self.current].active_child?;
+        self.last_append_time = None;
+
+        self.current = child;
+        self.nodes[child].event.as_ref()
+    }
+
+    // starts grouping the appended events into one undo step,
+    // `pos` is the cursor position before the group.
+    // nested transactions are merged into the outermost one.
+    pub fn begin(&mut self, pos: EditorCursorPos) {
+        self.transaction_depth += 1;
+        if self.transaction.is_none() {
+            self.transaction = Some((pos, vec![]));
+        }
+    }
+
+    // ends the transaction started by `begin`,
+    // `pos` is the cursor position after the group.
+    pub fn commit(&mut self, pos: EditorCursorPos) {
+        if self.transaction_depth == 0 {
+            return;
+        }
+        self.transaction_depth -= 1;
+        if self.transaction_depth > 0 {
+            return;
+        }
+
+        let Some((pos_before, events)) = self.transaction.take() else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+        self.push_event(EditorEvent {
+            op: EditorOperation::Group(events),
+            pos_before,
+            pos_after: pos,
+        });
+        self.last_append_time = None;
+    }
+
+    pub fn append(&mut self, ev: EditorEvent) {
+        if let Some((_, events)) = &mut self.transaction {
+            events.push(ev);
+            return;
+        }
+
+        let is_merging = self
+            .last_append_time
+            .is_some_and(|time| time.elapsed() < Self::MERGE_INTERVAL);
+        self.last_append_time = Some(Instant::now());
+
+        // only merge into the newest state without branches
+        let current_node = &mut self.nodes[self.current];
+        if is_merging && current_node.children.is_empty() {
+            if let Some(last_event) = &mut current_node.event {
+                if last_event.merge(&ev) {
+                    current_node.time = SystemTime::now();
+                    return;
+                }
+            }
+        }
+        self.push_event(ev);
+    }
+
+    fn push_event(&mut self, ev: EditorEvent) {
+        let index = self.nodes.len();
+        self.nodes.push(HistoryNode {
+            seq: self.next_seq,
+            event: Some(ev),
+            time: SystemTime::now(),
+            parent: self.current,
+            children: vec![],
+            active_child: None,
+        });
+        self.next_seq += 1;
+
+        let parent = &mut self.nodes[self.current];
+        parent.children.push(index);
+        parent.active_child = Some(index);
+        self.current = index;
+
+        if self.nodes.len() > self.max_size {
+            self.prune();
+        }
+    }
+
+    // --- --- --- --- --- ---
+
+    // returns the sequence number of the state created
+    // just before or after current state, across branches.
+    pub fn older_seq(&self) -> Option<usize> {
+        let index = self.current.checked_sub(1)?;
+        Some(self.nodes[index].seq)
+    }
+    pub fn newer_seq(&self) -> Option<usize> {
+        self.nodes.get(self.current + 1).map(|node| node.seq)
+    }
+
+    // returns the sequence number of the newest state
+    // reached before `time`, or the root state.
+    pub fn seq_at(&self, time: SystemTime) -> usize {
+        self.nodes
+            .iter()
+            .rev()
+            .find(|node| node.time <= time)
+            .unwrap_or(&self.nodes[0])
+            .seq
+    }
+
+    // returns the sequence number of the state
+    // `offset` ago or later than current state.
+    pub fn seq_from_now(&self, offset: Duration, is_earlier: bool) -> usize {
+        let current_time = self.nodes[self.current].time;
+        let target_time = if is_earlier {
+            current_time.checked_sub(offset).unwrap_or(SystemTime::UNIX_EPOCH)
         } else {
-            self.events.pop_back()
+            match current_time.checked_add(offset) {
+                Some(time) => time,
+                None => return self.nodes[self.nodes.len() - 1].seq,
+            }
         };
+        self.seq_at(target_time)
+    }
 
-        match option_op {
-            Some(op) => {
-                self.undo_events.push(op);
-                self.undo_events.last()
+    #[inline]
+    pub fn contains(&self, seq: usize) -> bool {
+        self.index_of(seq).is_some()
+    }
+
+    // moves one step from current state toward the state
+    // with sequence number `seq`, returns None when reached
+    // or the state does not exist.
+    pub fn step_to(&mut self, seq: usize) -> Option<HistoryStep<'_>> {
+        let target = self.index_of(seq)?;
+        if target == self.current {
+            return None;
+        }
+
+        // find the child of current state on the path to target
+        let mut index = target;
+        while index != 0 && self.nodes[index].parent != self.current {
+            index = self.nodes[index].parent;
+        }
+        if index != 0 && self.nodes[index].parent == self.current {
+            self.nodes[self.current].active_child = Some(index);
+            return self.redo().map(HistoryStep::Redo);
+        }
+        return self.undo().map(HistoryStep::Undo);
+    }
+
+    // returns the states in depth-first order for displaying,
+    // branches created later are indented deeper.
+    pub fn rows(&self) -> Vec<HistoryRow> {
+        let mut result = vec![];
+        let mut stack = vec![(0, 0, false)];
+        while let Some((index, depth, is_branch)) = stack.pop() {
+            let node = &self.nodes[index];
+            result.push(HistoryRow {
+                seq: node.seq,
+                depth,
+                branch_from: is_branch.then(|| self.nodes[node.parent].seq),
+                summary: match &node.event {
+                    Some(ev) => ev.op.to_string(),
+                    None => String::from("original"),
+                },
+                time: node.time,
+                is_current: index == self.current,
+            });
+
+            // the first child continues the current branch,
+            // push in reversed order to visit it first.
+            for (i, &child) in node.children.iter().enumerate().rev() {
+                let is_branch = i > 0;
+                let child_depth = if is_branch { depth + 1 } else { depth };
+                stack.push((child, child_depth, is_branch));
             }
-            None => None,
         }
+        return result;
     }
 
-    pub fn redo(&mut self) -> Option<&EditorEvent> {
-        match self.undo_events.pop() {
-            Some(op) => {
-                self.redo_events.push(op);
-                self.redo_events.last()
+    #[inline]
+    fn index_of(&self, seq: usize) -> Option<usize> {
+        self.nodes.binary_search_by_key(&seq, |node| node.seq).ok()
+    }
+
+    // removes the oldest states out of current branch,
+    // or the root state if there is only one branch.
+    fn prune(&mut self) {
+        let mut ancestors = vec![self.current];
+        let mut index = self.current;
+        while index != 0 {
+            index = self.nodes[index].parent;
+            ancestors.push(index);
+        }
+
+        let removable_leaf = (1..self.nodes.len())
+            .find(|i| self.nodes[*i].children.is_empty() && !ancestors.contains(i));
+        match removable_leaf {
+            Some(leaf) => {
+                let parent = self.nodes[leaf].parent;
+                let parent_node = &mut self.nodes[parent];
+                parent_node.children.retain(|&child| child != leaf);
+                if parent_node.active_child == Some(leaf) {
+                    parent_node.active_child = parent_node.children.last().copied();
+                }
+                self.remove_node(leaf);
+            }
+            None => {
+                // there is only one branch, the child of
+                // root becomes the new root.
+                let Some(&new_root) = self.nodes[0].children.first() else {
+                    return;
+                };
+                self.nodes[new_root].event = None;
+                self.remove_node(0);
             }
-            None => None,
         }
     }
 
-    pub fn append(&mut self, ev: EditorEvent) {
-        self.undo_events.clear();
-        self.redo_events.clear();
-        self.events.push_back(ev);
+    // removes the node and fixes the indexes after it.
+    fn remove_node(&mut self, removed: usize) {
+        let shift = |index: usize| if index > removed { index - 1 } else { index };
+        self.nodes.remove(removed);
+        for node in self.nodes.iter_mut() {
+            node.parent = shift(node.parent);
+            node.children = node.children.iter().map(|&i| shift(i)).collect();
+            node.active_child = node.active_child.map(shift);
+        }
+        self.current = shift(self.current);
+        self.nodes[0].parent = 0;
+    }
+
+    // --- --- --- --- --- ---
+
+    // encodes the undo tree into lines, the first line is
+    // `<current index> <next seq>`, followed by a line for each state:
+    // `<seq> <time in ms> <parent> <active child or -> <event or ->`.
+    pub fn encode(&self) -> String {
+        let mut lines = vec![format!("{} {}", self.current, self.next_seq)];
+        for node in &self.nodes {
+            let time = node
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let mut tokens = vec![
+                node.seq.to_string(),
+                time.to_string(),
+                node.parent.to_string(),
+                node.active_child
+                    .map(|i| i.to_string())
+                    .unwrap_or("-".to_owned()),
+            ];
+            match &node.event {
+                Some(ev) => ev.encode(&mut tokens),
+                None => tokens.push("-".to_owned()),
+            }
+            lines.push(tokens.join(" "));
+        }
+        return lines.join("\n");
+    }
+
+    // returns None if the text is not a valid encoded undo tree.
+    pub fn decode(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        let mut head = lines.next()?.split(' ');
+        let current = head.next()?.parse::<usize>().ok()?;
+        let next_seq = head.next()?.parse::<usize>().ok()?;
+
+        let mut nodes: Vec<HistoryNode> = vec![];
+        for (index, line) in lines.enumerate() {
+            let mut tokens = line.split(' ');
+            let seq = tokens.next()?.parse::<usize>().ok()?;
+            let time = tokens.next()?.parse::<u64>().ok()?;
+            let parent = tokens.next()?.parse::<usize>().ok()?;
+            let active_child = match tokens.next()? {
+                "-" => None,
+                token => Some(token.parse::<usize>().ok()?),
+            };
+            let is_root = index == 0;
+            let event = if is_root {
+                (tokens.next()? == "-").then_some(())?;
+                None
+            } else {
+                Some(EditorEvent::decode(&mut tokens)?)
+            };
 
-        if self.events.len() > Self::MAX_CACHED_EVENT {
-            self.events.pop_front();
+            // states are sorted and parents come first
+            let is_sorted = nodes.last().is_none_or(|last| last.seq < seq);
+            if !is_sorted || (!is_root && parent >= index) || seq >= next_seq {
+                return None;
+            }
+            if !is_root {
+                nodes[parent].children.push(index);
+            }
+            nodes.push(HistoryNode {
+                seq,
+                event,
+                time: UNIX_EPOCH + Duration::from_millis(time),
+                parent: if is_root { 0 } else { parent },
+                children: vec![],
+                active_child,
+            });
+        }
+
+        let is_valid = current < nodes.len()
+            && nodes.iter().all(|node| match node.active_child {
+                Some(child) => node.children.contains(&child),
+                None => true,
+            });
+        if !is_valid {
+            return None;
         }
+        return Some(Self {
+            nodes,
+            current,
+            next_seq,
+
+            last_append_time: None,
+
+            transaction: None,
+            transaction_depth: 0,
+
+            max_size: Self::DEFAULT_MAX_SIZE,
+        });
+    }
+}
+
+#[test]
+fn history_merge_test() {
+    let mut history = EditorHistory::new();
+    let pos = |col| EditorCursorPos { row: 1, col };
+    for (i, ch) in "ab cd".chars().enumerate() {
+        history.append(EditorEvent {
+            op: EditorOperation::InsertChar(ch),
+            pos_before: pos(i + 1),
+            pos_after: pos(i + 2),
+        });
+    }
+    // cursor jump
+    history.append(EditorEvent {
+        op: EditorOperation::InsertChar('e'),
+        pos_before: pos(1),
+        pos_after: pos(2),
+    });
+
+    let texts = history
+        .rows()
+        .into_iter()
+        .map(|row| row.summary)
+        .collect::<Vec<String>>();
+    assert_eq!(
+        texts,
+        vec!["original", "insert \"ab\"", "insert \" cd\"", "insert \"e\""]
+    );
+}
+
+#[test]
+fn history_transaction_test() {
+    let mut history = EditorHistory::new();
+    let pos = |col| EditorCursorPos { row: 1, col };
+    history.begin(pos(1));
+    history.begin(pos(1));
+    for col in [3, 1] {
+        history.append(EditorEvent {
+            op: EditorOperation::Replace("a".to_owned(), "b".to_owned()),
+            pos_before: pos(col),
+            pos_after: pos(col),
+        });
     }
+    history.commit(pos(1));
+    history.commit(pos(2));
+
+    let ev = history.undo().unwrap();
+    assert_eq!(ev.pos_before, pos(1));
+    assert_eq!(ev.pos_after, pos(2));
+    let EditorOperation::Group(events) = ev.op.rev() else {
+        unreachable!();
+    };
+    // reversed events are in reversed order
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].pos_before, pos(1));
+    assert_eq!(events[1].pos_before, pos(3));
+    assert!(history.undo().is_none());
+}
+
+#[test]
+fn history_tree_test() {
+    let mut history = EditorHistory::new();
+    let pos = |col| EditorCursorPos { row: 1, col };
+    let insert_line = |history: &mut EditorHistory| {
+        history.append(EditorEvent {
+            op: EditorOperation::InsertLine,
+            pos_before: pos(1),
+            pos_after: pos(1),
+        })
+    };
+
+    // 0 - 1 - 2
+    //      \
+    //       3
+    insert_line(&mut history);
+    insert_line(&mut history);
+    history.undo();
+    insert_line(&mut history);
+    assert_eq!(history.nodes[history.current].seq, 3);
+    assert_eq!(history.older_seq(), Some(2));
+
+    let depths = history
+        .rows()
+        .iter()
+        .map(|row| (row.seq, row.depth))
+        .collect::<Vec<(usize, usize)>>();
+    assert_eq!(depths, vec![(0, 0), (1, 0), (2, 0), (3, 1)]);
+
+    // travel from 3 to 2 through 1
+    assert!(matches!(history.step_to(2), Some(HistoryStep::Undo(_))));
+    assert_eq!(history.nodes[history.current].seq, 1);
+    assert!(matches!(history.step_to(2), Some(HistoryStep::Redo(_))));
+    assert_eq!(history.nodes[history.current].seq, 2);
+    assert!(history.step_to(2).is_none());
+
+    // redo goes into the last visited branch
+    history.undo();
+    history.redo();
+    assert_eq!(history.nodes[history.current].seq, 2);
+
+    assert_eq!(history.seq_from_now(Duration::from_secs(3600), true), 0);
+    assert_eq!(history.seq_from_now(Duration::from_secs(3600), false), 3);
+}
+
+#[test]
+fn history_encode_test() {
+    let mut history = EditorHistory::new();
+    let pos = |col| EditorCursorPos { row: 1, col };
+    for ch in ['a', 'b'] {
+        history.append(EditorEvent {
+            op: EditorOperation::InsertChar(ch),
+            pos_before: pos(1),
+            pos_after: pos(2),
+        });
+        history.undo();
+    }
+
+    let text = history.encode();
+    let decoded = EditorHistory::decode(&text).unwrap();
+    assert_eq!(decoded.encode(), text);
+    assert_eq!(decoded.nodes[0].children, vec![1, 2]);
+    assert_eq!(decoded.nodes[0].active_child, Some(2));
+
+    assert!(EditorHistory::decode("").is_none());
+    // current state out of range
+    assert!(text.starts_with("0 3\n"));
+    assert!(EditorHistory::decode(&text.replacen("0 3", "9 3", 1)).is_none());
 }
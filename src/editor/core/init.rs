@@ -1,8 +1,6 @@
 use std::io;
 
-use crossterm::style::Stylize;
-
-use crate::{utils::{Cursor, Terminal}, editor::core::color::EditorColor};
+use crate::{utils::{Cursor, Terminal}, editor::theme::Theme};
 
 pub struct EditorInit;
 
@@ -17,19 +15,21 @@ impl EditorInit {
         let padding_width2 = term_width - padding_width1 - elements_width;
         let (padding_str1, padding_str2) = (" ".repeat(padding_width1), " ".repeat(padding_width2));
 
+        let theme = Theme::current();
         print!(
             "{}{}{}{}",
-            padding_str1.on_white(),
-            title_str.bold().black().on_white(),
-            padding_str2.on_white(),
-            EditorColor::highlight_style(esc_button_str),
+            theme.title.apply(padding_str1),
+            theme.title.apply(title_str),
+            theme.title.apply(padding_str2),
+            theme.title_button.apply(esc_button_str),
         );
     }
 
     pub fn display_border() -> io::Result<()> {
         // print left and right border
+        let border = Theme::current().border.apply("  ");
         for _ in 1..Terminal::height() {
-            print!("{}", "  ".on_white());
+            print!("{}", border);
             Cursor::down(1)?;
             Cursor::move_to_col(0)?;
         }
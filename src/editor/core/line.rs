@@ -1,7 +1,9 @@
 use std::io;
 
+use crossterm::style::ContentStyle;
+
 use crate::{
-    editor::{core::color::EditorColor, direction::Direction, text_area::TextArea},
+    editor::{direction::Direction, text_area::TextArea, theme::Theme},
     utils::{number_bit_count, Cursor, Terminal},
 };
 
@@ -17,11 +19,11 @@ pub struct EditorLine {
 impl EditorLine {
     #[inline]
     pub fn is_at_line_start(&self) -> io::Result<bool> {
-        Ok(self.text_area.state_left()?.is_at_area_start)
+        self.text_area.is_at_area_start()
     }
     #[inline]
     pub fn is_at_line_end(&self) -> io::Result<bool> {
-        Ok(self.text_area.state_right()?.is_at_area_end)
+        self.text_area.is_at_area_end()
     }
 }
 
@@ -41,6 +43,15 @@ impl EditorLine {
     pub fn move_cursor_horizontal(&mut self, dir: Direction) -> io::Result<()> {
         self.text_area.move_cursor_horizontal(dir, true)
     }
+    // moves the cursor to the char at byte `index`
+    #[inline]
+    pub fn move_cursor_to(&mut self, index: usize) -> io::Result<()> {
+        self.text_area.move_cursor_to(index, true)
+    }
+    #[inline]
+    pub fn snap_cursor(&mut self) -> io::Result<()> {
+        self.text_area.snap_cursor(true)
+    }
 
     #[inline]
     pub fn jump_to_word_edge(&mut self, dir: Direction) -> io::Result<()> {
@@ -52,9 +63,19 @@ impl EditorLine {
         self.text_area.insert_char(ch, true)
     }
     #[inline]
+    pub fn insert_str(&mut self, str: &str) -> io::Result<()> {
+        self.text_area.insert_str(str, true)
+    }
+    #[inline]
     pub fn delete_char(&mut self) -> io::Result<Option<char>> {
         self.text_area.delete_char(true)
     }
+
+    // the chars before and after cursor
+    pub fn chars_around_cursor(&self) -> io::Result<(Option<char>, Option<char>)> {
+        let (before, after) = self.content().split_at(self.cursor_pos()?);
+        return Ok((before.chars().last(), after.chars().next()));
+    }
 }
 
 impl EditorLine {
@@ -81,23 +102,6 @@ impl EditorLine {
         return Ok(());
     }
 
-    pub fn find_all(&self, pat: &str) -> Option<Vec<usize>> {
-        let mut text = self.content();
-        let mut pos_offset = 0;
-        let mut result_vec = vec![];
-        while let Some(pos) = text.find(pat) {
-            result_vec.push(pos + pos_offset);
-            pos_offset += pos + pat.len();
-            text = &text[(pos + pat.len())..];
-        }
-
-        if result_vec.is_empty() {
-            return None;
-        } else {
-            return Some(result_vec);
-        }
-    }
-
     fn render_label(&self) -> io::Result<()> {
         let saved_cursor_pos = Cursor::pos_col()?;
         Cursor::move_to_col(0)?;
@@ -105,10 +109,11 @@ impl EditorLine {
         let index_width = number_bit_count(self.cached_index);
         let space_width = self.cached_label_width - index_width;
         let line_label_str = format!("{}{}", self.cached_index, " ".repeat(space_width));
+        let theme = Theme::current();
         let line_label_styled = if self.is_active {
-            EditorColor::line_active_style(&*line_label_str)
+            theme.line_label_active.apply(&*line_label_str)
         } else {
-            EditorColor::line_disabled_style(&*line_label_str)
+            theme.line_label.apply(&*line_label_str)
         };
         print!("{}", line_label_styled);
         Cursor::move_to_col(saved_cursor_pos)?;
@@ -134,12 +139,31 @@ impl EditorLine {
     pub fn cursor_pos(&self) -> io::Result<usize> {
         self.text_area.cursor_pos()
     }
+    // the column of cursor with tabs expanded
+    #[inline]
+    pub fn cursor_col(&self) -> io::Result<usize> {
+        self.text_area.cursor_col()
+    }
 
     #[inline]
     pub fn content(&self) -> &str {
         self.text_area.content()
     }
 
+    #[inline]
+    pub fn set_highlight(&mut self, range: Option<(usize, usize)>) {
+        self.text_area.set_highlight(range);
+    }
+    #[inline]
+    pub fn set_highlight_with(&mut self, range: Option<(usize, usize)>, style: ContentStyle) {
+        self.text_area.set_highlight_with(range, style);
+    }
+
+    #[inline]
+    pub fn set_content(&mut self, str: &str) {
+        self.text_area.set_content(str);
+    }
+
     #[inline]
     pub fn push_str(&mut self, str: &str) {
         self.text_area.push_str(str);
@@ -155,11 +179,3 @@ impl EditorLine {
         self.text_area.len()
     }
 }
-
-#[test]
-fn editorline_find_all_test() {
-    let mut line = EditorLine::new(0, false);
-    line.push_str("abc  abc  abc");
-
-    assert_eq!(line.find_all("abc"), Some(vec![0, 5, 10]));
-}
@@ -1,21 +1,20 @@
-mod color;
 mod dashboard;
 mod event;
 mod history;
 mod init;
 mod line;
 mod state;
+mod undo_cache;
 
-use std::{fs, io, path::Path};
-
-use crossterm::{
-    event::{KeyCode, KeyEvent, KeyModifiers},
-    execute,
-    style::Stylize,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
-use crate::utils::{number_bit_count, Cursor, Terminal};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::utils::{content_hash, number_bit_count, Cursor, Terminal, TerminalInput};
 
 use dashboard::EditorDashboard;
 use init::EditorInit;
@@ -23,15 +22,31 @@ use line::EditorLine;
 pub use state::EditorState;
 
 use self::{
-    color::EditorColor,
     event::{EditorEvent, EditorOperation},
-    history::EditorHistory,
+    history::{EditorHistory, HistoryStep},
 };
 
-use super::{components::{FileOpener, Finder}, direction::Direction};
+use super::{
+    comment,
+    components::{
+        FileOpener, FileSearcher, Finder, HistoryTarget, HistoryViewer, RecoverAction,
+        SwapRecoverer,
+    },
+    direction::Direction,
+    indent::{self, IndentStyle},
+    language::Language,
+    message::EditorMessage,
+    pairs,
+    pattern::SearchPattern,
+    swap_file,
+    text_area::TextArea,
+    theme::Theme,
+};
 use super::{
     components::{EditorComponentManager, FileSaver, LineComponent, Positioner},
+    config::{ConfigError, EditorConfig, FileProperties, LineEnding},
     cursor_pos::EditorCursorPos,
+    keymap::{EditorAction, EditorInput, KeyChord, KeyMatch},
 };
 
 pub struct Editor {
@@ -40,10 +55,30 @@ pub struct Editor {
 
     overflow_top: usize,
     overflow_bottom: usize,
+    // the row where the selection of lines starts,
+    // the selection ends at the current line.
+    selection_anchor: Option<usize>,
+    // the indent style detected from the file, or set by config
+    indent_style: IndentStyle,
+    // the properties from `.editorconfig` for current file
+    file_properties: FileProperties,
+    // the position of highlighted bracket matching the one at cursor
+    matched_bracket: Option<EditorCursorPos>,
+    // the count of closers inserted with their openers after
+    // cursor in current line, typing them steps over.
+    auto_closed: usize,
 
     components: EditorComponentManager,
     history: EditorHistory,
     dashboard: EditorDashboard,
+
+    last_autosave_time: Instant,
+    // the hash of content in swap file written by current process
+    swap_hash: Option<u64>,
+
+    config: EditorConfig,
+    // the pressed keys of an unfinished key sequence
+    pending_keys: Vec<KeyChord>,
 }
 
 // base value calculating methods
@@ -96,7 +131,7 @@ impl Editor {
             for _ in 0..diff {
                 Cursor::move_to_col(0)?;
                 Terminal::clear_after_cursor()?;
-                print!("{}", " ".repeat(label_width).on_grey());
+                print!("{}", Theme::current().line_label.apply(" ".repeat(label_width)));
                 Cursor::down(1)?;
             }
         }
@@ -111,7 +146,7 @@ impl Editor {
 
         self.index = 1;
         self.overflow_top = 0;
-        self.overflow_bottom = 0;
+        self.overflow_bottom = self.lines.len().saturating_sub(self.visible_area_height());
         return Ok(());
     }
 
@@ -151,8 +186,7 @@ impl Editor {
         }
 
         self.lines[self.index - 1].disable()?; // disable current line
-        let (cursor_pos_row, cursor_pos_col) = (Cursor::pos_row()?, Cursor::pos_col()?);
-        let label_width = self.label_width();
+        let cursor_pos_row = Cursor::pos_row()?;
         let target_line = match dir {
             Direction::Up => {
                 let is_at_top_side = cursor_pos_row == 1;
@@ -182,10 +216,9 @@ impl Editor {
             }
             _ => unreachable!(),
         };
-        // if target_line is shorter than current line
-        if cursor_pos_col > target_line.len() + label_width {
-            Cursor::left(cursor_pos_col - label_width - target_line.len())?;
-        }
+        // if target_line is shorter than current line,
+        // or the cursor is inside a tab of target_line
+        target_line.snap_cursor()?;
         target_line.active()?;
         return Ok(());
     }
@@ -228,6 +261,23 @@ impl Editor {
         return Ok(());
     }
 
+    // inserts both chars and moves the cursor between them
+    fn insert_pair(&mut self, open: char, close: char) -> io::Result<()> {
+        let current_line = &mut self.lines[self.index - 1];
+        current_line.insert_char(open)?;
+        current_line.insert_char(close)?;
+        current_line.move_cursor_horizontal(Direction::Left)?;
+        return Ok(());
+    }
+    // deletes the chars before and after cursor
+    fn delete_pair(&mut self) -> io::Result<()> {
+        let current_line = &mut self.lines[self.index - 1];
+        current_line.move_cursor_horizontal(Direction::Right)?;
+        current_line.delete_char()?;
+        current_line.delete_char()?;
+        return Ok(());
+    }
+
     fn delete_line(&mut self) -> io::Result<()> {
         let label_width = self.label_width_with(self.lines.len() - 1);
         let (previous_line, deleted_line) = {
@@ -237,13 +287,11 @@ impl Editor {
             (previous_line, removed_line)
         };
         if let Some(line) = previous_line {
+            let joint_pos = line.len();
             line.push_str(deleted_line.content());
             line.move_cursor_to_end(label_width)?;
             line.active()?;
-
-            for _ in 0..deleted_line.len() {
-                line.move_cursor_horizontal(Direction::Left)?;
-            }
+            line.move_cursor_to(joint_pos)?;
         }
         self.index -= 1;
         // scroll
@@ -266,11 +314,33 @@ impl Editor {
             return Ok(());
         }
 
+        // deletes both chars of an empty pair
+        let (before, after) = self.lines[self.index - 1].chars_around_cursor()?;
+        if self.config.auto_pairs && pairs::is_empty_pair(before, after) {
+            let (open, close) = (before.unwrap(), after.unwrap());
+            self.append_event(EditorOperation::DeletePair(open, close), |e| {
+                e.delete_pair()
+            })?;
+            self.auto_closed = self.auto_closed.saturating_sub(1);
+            return Ok(());
+        }
+
         let pos_before = self.cursor_pos()?;
+        let indent_width = self.indent_width();
         let current_line = &mut self.lines[self.index - 1];
 
+        // in leading whitespace, removes one indent level
+        let prefix = &current_line.content()[..current_line.cursor_pos()?];
+        let outdent_len = match self.config.auto_indent && prefix.trim().is_empty() {
+            true => indent::outdent_len(prefix, indent_width),
+            false => 0,
+        };
+
         if current_line.is_at_line_start()? {
             self.append_event(EditorOperation::DeleteLine, |e| e.delete_line())?;
+        } else if outdent_len > 1 {
+            let removed = prefix[(prefix.len() - outdent_len)..].to_owned();
+            self.delete_text(&removed)?;
         } else {
             let deleted_ch = current_line.delete_char()?;
             let pos_after = self.cursor_pos()?;
@@ -285,16 +355,247 @@ impl Editor {
         return Ok(());
     }
 
-    fn replace(&mut self, count: usize, to: &str) -> io::Result<()> {
-        let current_line = &mut self.lines[self.index - 1];
-        for _ in 0..count {
-            current_line.move_cursor_horizontal(Direction::Right)?;
-            current_line.delete_char()?;
+    // replaces the text `from` after cursor with `to`,
+    // both of them can contain line breaks.
+    // the cursor stays at the start of replaced text.
+    fn replace(&mut self, from: &str, to: &str) -> io::Result<()> {
+        let pos = self.cursor_pos()?;
+        let (start_index, start_col) = (pos.row - 1, pos.col - 1);
+
+        // find out the end of `from` in lines
+        let from_lines = from.split('\n').collect::<Vec<&str>>();
+        let end_index = start_index + from_lines.len() - 1;
+        let end_col = match from_lines.len() {
+            1 => start_col + from.len(),
+            _ => from_lines.last().unwrap().len(),
+        };
+
+        let new_text = format!(
+            "{}{}{}",
+            &self.lines[start_index].content()[..start_col],
+            to,
+            &self.lines[end_index].content()[end_col..],
+        );
+        let new_line_count = self.lines.len() - from_lines.len() + to.split('\n').count();
+        let label_width = self.label_width_with(new_line_count);
+        let new_lines = new_text.split('\n').map(|l| {
+            let mut new_line = EditorLine::new(label_width, false);
+            new_line.push_str(l);
+            new_line
+        });
+        self.lines.splice(start_index..=end_index, new_lines);
+
+        self.jump_to(pos)?;
+        return Ok(());
+    }
+
+    // replaces the replacer's matching text at cursor position
+    // and records the actually replaced text.
+    // returns the replacement text if replaced.
+    fn replace_at_cursor(&mut self) -> io::Result<Option<String>> {
+        let pos = self.cursor_pos()?;
+        let text = self.content_with("\n");
+        let offset = self.offset_of(pos);
+        let Some((from, to)) = self.components.replacer.expand_at(&text, offset) else {
+            return Ok(None);
+        };
+        self.append_event(EditorOperation::Replace(from.clone(), to.clone()), |e| {
+            e.replace(&from, &to)
+        })?;
+        return Ok(Some(to));
+    }
+
+    // returns the position and length of the first matching
+    // starts at or after `offset`, recomputed from the buffer.
+    fn find_match_from(
+        &self,
+        pattern: &SearchPattern,
+        offset: usize,
+    ) -> Option<(EditorCursorPos, usize)> {
+        let text = self.content_with("\n");
+        let (start, end) = pattern.find_from(&text, offset)?;
+        return Some((self.pos_of(start), end - start));
+    }
+
+    // jumps to the matching at `pos` and highlights it.
+    fn focus_match(&mut self, pos: EditorCursorPos) -> io::Result<()> {
+        self.clear_highlight();
+        let option_match = match self.components.replacer.pattern() {
+            Some(pattern) => self.find_match_from(pattern, self.offset_of(pos)),
+            None => None,
+        };
+        if let Some((match_pos, len)) = option_match.filter(|(p, _)| *p == pos) {
+            let text = self.content_with("\n");
+            let start = self.offset_of(match_pos);
+            for (i, part) in text[start..(start + len)].split('\n').enumerate() {
+                let line_start = if i == 0 { pos.col - 1 } else { 0 };
+                let line = &mut self.lines[pos.row - 1 + i];
+                line.set_highlight(Some((line_start, line_start + part.len())));
+            }
         }
+        self.jump_to(pos)?;
+        return Ok(());
+    }
 
-        for ch in to.chars().rev() {
-            current_line.insert_char(ch)?;
-            current_line.move_cursor_horizontal(Direction::Left)?;
+    fn clear_highlight(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.set_highlight(None);
+        }
+        self.matched_bracket = None;
+    }
+
+    // the position of the bracket at or before cursor and
+    // the position of its matching bracket.
+    fn bracket_pair_at_cursor(&self) -> io::Result<Option<(EditorCursorPos, EditorCursorPos)>> {
+        let current_line = &self.lines[self.index - 1];
+        let index = current_line.cursor_pos()?;
+        let (before, after) = current_line.chars_around_cursor()?;
+        let bracket_index = match (before, after) {
+            (_, Some(ch)) if pairs::is_bracket(ch) => index,
+            (Some(ch), _) if pairs::is_bracket(ch) => index - ch.len_utf8(),
+            _ => return Ok(None),
+        };
+
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.content()).collect();
+        let row = self.index - 1;
+        let pair = pairs::find_match(&lines, row, bracket_index, self.language());
+        let pos = |(row, index): (usize, usize)| EditorCursorPos {
+            row: row + 1,
+            col: index + 1,
+        };
+        return Ok(pair.map(|matched| (pos((row, bracket_index)), pos(matched))));
+    }
+
+    // highlights the bracket matching the one at cursor in normal mode
+    fn refresh_bracket_match(&mut self) -> io::Result<()> {
+        let is_using_component =
+            self.components.use_line_component || self.components.use_screen_component;
+        let matched = match is_using_component || self.selection_anchor.is_some() {
+            true => None,
+            false => self.bracket_pair_at_cursor()?.map(|(_, matched)| matched),
+        };
+        if matched == self.matched_bracket {
+            return Ok(());
+        }
+
+        // the highlighted line may have moved after editing
+        self.clear_highlight();
+        if let Some(pos) = matched {
+            let range = Some((pos.col - 1, pos.col));
+            let style = Theme::current().bracket_match;
+            self.lines[pos.row - 1].set_highlight_with(range, style);
+        }
+        self.matched_bracket = matched;
+        self.render_all()?;
+        return Ok(());
+    }
+
+    // moves the cursor to the bracket matching the one at cursor
+    fn jump_to_matching_bracket(&mut self) -> io::Result<()> {
+        if let Some((_, matched)) = self.bracket_pair_at_cursor()? {
+            self.jump_to(matched)?;
+        }
+        return Ok(());
+    }
+
+    // replaces or skips the current matching of replacer,
+    // then moves to the next matching.
+    fn replace_step(&mut self, is_replacing: bool) -> io::Result<()> {
+        let Some(pos) = self.components.replacer.current() else {
+            return Ok(());
+        };
+        let Some(pattern) = self.components.replacer.pattern() else {
+            return Ok(());
+        };
+        let offset = self.offset_of(pos);
+        let match_len = match self.find_match_from(pattern, offset) {
+            Some((match_pos, len)) if match_pos == pos => len,
+            _ => 0,
+        };
+
+        let mut next_offset = offset + match_len;
+        if is_replacing {
+            self.jump_to(pos)?;
+            if let Some(to) = self.replace_at_cursor()? {
+                self.components.replacer.push_replaced(pos);
+                next_offset = offset + to.len();
+            }
+        }
+
+        let next_match = match self.components.replacer.pattern() {
+            Some(pattern) => self.find_match_from(pattern, next_offset),
+            None => None,
+        };
+        let next_pos = next_match.map(|(p, _)| p);
+        self.components.replacer.set_current(next_pos);
+        match next_pos {
+            Some(pos) => self.focus_match(pos)?,
+            None => {
+                self.clear_highlight();
+                self.jump_to(self.cursor_pos()?)?;
+            }
+        }
+        return Ok(());
+    }
+
+    fn replace_all_remaining(&mut self) -> io::Result<()> {
+        let Some(pos) = self.components.replacer.current() else {
+            return Ok(());
+        };
+        let Some(pattern) = self.components.replacer.pattern() else {
+            return Ok(());
+        };
+
+        // collects all the remaining matchings on the text built once
+        let text = self.content_with("\n");
+        let mut replacements = vec![];
+        let mut offset = self.offset_of(pos);
+        while let Some((start, end)) = pattern.find_from(&text, offset) {
+            let Some((from, to)) = self.components.replacer.expand_at(&text, start) else {
+                break;
+            };
+            replacements.push((start, from, to));
+
+            // steps over empty matchings
+            offset = match (start == end, text[end..].chars().next()) {
+                (false, _) => end,
+                (true, Some(ch)) => end + ch.len_utf8(),
+                (true, None) => break,
+            };
+        }
+        let (Some(first), Some(last)) = (replacements.first(), replacements.last()) else {
+            self.components.replacer.set_current(None);
+            return Ok(());
+        };
+        let (span_start, span_end) = (first.0, last.0 + last.1.len());
+
+        // builds the replaced text of the whole span in a single pass,
+        // and replaces the span as one event.
+        let mut replaced = String::with_capacity(span_end - span_start);
+        let mut copied = span_start;
+        for (start, from, to) in replacements.iter() {
+            replaced.push_str(&text[copied..*start]);
+            replaced.push_str(to);
+            copied = start + from.len();
+        }
+        let from = &text[span_start..span_end];
+        let op = EditorOperation::Replace(from.to_owned(), replaced.clone());
+        let first_pos = self.pos_of(span_start);
+        self.transaction(|e| {
+            e.jump_to(first_pos)?;
+            e.append_event(op, |e| e.replace(from, &replaced))?;
+            return Ok(());
+        })?;
+        self.components.replacer.set_current(None);
+        return Ok(());
+    }
+
+    // undoes the last replacing in current replacing.
+    fn undo_replacement(&mut self) -> io::Result<()> {
+        if let Some(pos) = self.components.replacer.pop_replaced() {
+            self.undo()?;
+            self.components.replacer.set_current(Some(pos));
+            self.focus_match(pos)?;
         }
         return Ok(());
     }
@@ -308,11 +609,33 @@ impl Editor {
                 let current_line = &mut self.lines[self.index - 1];
                 current_line.delete_char()?;
             }
+            EditorOperation::InsertText(text) => {
+                let current_line = &mut self.lines[self.index - 1];
+                current_line.insert_str(&text)?;
+            }
+            EditorOperation::DeleteText(text) => {
+                let current_line = &mut self.lines[self.index - 1];
+                for _ in text.chars() {
+                    current_line.delete_char()?;
+                }
+            }
             EditorOperation::InsertLine => self.insert_line()?,
             EditorOperation::DeleteLine => self.delete_line()?,
-
-            EditorOperation::Replace(from, to) => {
-                self.replace(from.len(), to.as_str())?;
+            EditorOperation::InsertPair(open, close) => self.insert_pair(open, close)?,
+            EditorOperation::DeletePair(..) => self.delete_pair()?,
+
+            EditorOperation::Replace(from, to) => self.replace(&from, &to)?,
+            EditorOperation::IndentLines(row, texts) => self.change_indents(row, &texts, true)?,
+            EditorOperation::OutdentLines(row, texts) => self.change_indents(row, &texts, false)?,
+            EditorOperation::InsertLines(row, texts) => self.insert_lines(row, &texts)?,
+            EditorOperation::RemoveLines(row, texts) => self.remove_lines(row, texts.len())?,
+            EditorOperation::MoveLines(row, count, dir) => self.move_lines(row, count, dir)?,
+
+            EditorOperation::Group(events) => {
+                for ev in events {
+                    self.jump_to(ev.pos_before)?;
+                    self.exec_operation(ev.op)?;
+                }
             }
         }
         return Ok(());
@@ -335,23 +658,701 @@ impl Editor {
         return Ok(());
     }
 
+    // inserts `text` without line breaks as one event
+    fn insert_text(&mut self, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.append_event(EditorOperation::InsertText(text.to_owned()), |e| {
+            e.lines[e.index - 1].insert_str(text)
+        })?;
+        return Ok(());
+    }
+    // deletes `text` before cursor as one event
+    fn delete_text(&mut self, text: &str) -> io::Result<()> {
+        self.append_event(EditorOperation::DeleteText(text.to_owned()), |e| {
+            let current_line = &mut e.lines[e.index - 1];
+            for _ in text.chars() {
+                current_line.delete_char()?;
+            }
+            return Ok(());
+        })?;
+        return Ok(());
+    }
+
+    // inserts spaces to the next tab stop, or `\t` for hard tabs.
+    fn insert_tab(&mut self) -> io::Result<()> {
+        let text = match self.indent_style {
+            IndentStyle::Spaces(width) => {
+                let col = self.lines[self.index - 1].cursor_col()?;
+                " ".repeat(width - col % width)
+            }
+            IndentStyle::Tabs => "\t".to_owned(),
+        };
+        return self.insert_text(&text);
+    }
+
+    #[inline]
+    fn indent_style(&self) -> IndentStyle {
+        self.indent_style
+    }
+    // the columns of one indent level
+    #[inline]
+    fn indent_width(&self) -> usize {
+        match self.indent_style {
+            IndentStyle::Spaces(width) => width,
+            IndentStyle::Tabs => self.tab_width(),
+        }
+    }
+
+    // the columns between tab stops
+    #[inline]
+    fn tab_width(&self) -> usize {
+        self.file_properties
+            .tab_width()
+            .unwrap_or(self.config.tab_width)
+    }
+
+    #[inline]
+    fn line_ending(&self) -> LineEnding {
+        self.file_properties
+            .line_ending
+            .unwrap_or(self.config.line_ending)
+    }
+
+    fn set_indent_style(&mut self, style: IndentStyle) -> io::Result<()> {
+        self.indent_style = style;
+        self.dashboard.set_indent_style(style);
+        self.dashboard.render()?;
+        return Ok(());
+    }
+
+    // converts the indent of all lines between tabs and
+    // spaces of `tab_width`, as one event.
+    fn retab(&mut self) -> io::Result<()> {
+        let from_width = self.indent_width();
+        let style = match self.indent_style {
+            IndentStyle::Tabs => IndentStyle::Spaces(self.tab_width()),
+            IndentStyle::Spaces(_) => IndentStyle::Tabs,
+        };
+        let (old_indents, new_indents): (Vec<String>, Vec<String>) = self
+            .lines
+            .iter()
+            .map(|line| {
+                let prefix = indent::leading_whitespace(line.content());
+                (prefix.to_owned(), indent::retab(prefix, from_width, style))
+            })
+            .unzip();
+        self.set_indent_style(style)?;
+        self.dashboard
+            .set_message(EditorMessage::info(format!("Indent with {}", style)))?;
+        if old_indents == new_indents {
+            return Ok(());
+        }
+
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            e.append_event(EditorOperation::OutdentLines(1, old_indents.clone()), |e| {
+                e.change_indents(1, &old_indents, false)
+            })?;
+            e.append_event(EditorOperation::IndentLines(1, new_indents.clone()), |e| {
+                e.change_indents(1, &new_indents, true)
+            })
+        })?;
+        return Ok(());
+    }
+
+    #[inline]
+    fn language(&self) -> Language {
+        Language::from_path(Path::new(self.components.file_saver.path()))
+    }
+
+    // inserts a line break, the new line keeps the indent of
+    // current line, and is indented one more level if a block
+    // opens before cursor. between brackets like `{|}`, the
+    // closing bracket is moved to another line.
+    fn break_line(&mut self) -> io::Result<()> {
+        let current_line = &self.lines[self.index - 1];
+        let (before, after) = current_line
+            .content()
+            .split_at(current_line.cursor_pos()?);
+        let base_indent = indent::leading_whitespace(before).to_owned();
+        let (indent, closing_indent) = match indent::opens_block(before, self.language()) {
+            true if indent::is_between_pair(before, after) => {
+                let indent = base_indent.clone() + &self.indent_style().unit();
+                (indent, Some(base_indent))
+            }
+            true => (base_indent + &self.indent_style().unit(), None),
+            false => (base_indent, None),
+        };
+        if !self.config.auto_indent || (indent.is_empty() && closing_indent.is_none()) {
+            return self.append_event(EditorOperation::InsertLine, |e| e.insert_line());
+        }
+
+        self.transaction(|e| {
+            e.append_event(EditorOperation::InsertLine, |e| e.insert_line())?;
+            e.insert_text(&indent)?;
+            if let Some(closing_indent) = closing_indent {
+                let pos = e.cursor_pos()?;
+                e.append_event(EditorOperation::InsertLine, |e| e.insert_line())?;
+                e.insert_text(&closing_indent)?;
+                e.jump_to(pos)?;
+            }
+            return Ok(());
+        })?;
+        return Ok(());
+    }
+
+    // the first and last row of selected lines
+    fn selected_rows(&self) -> Option<(usize, usize)> {
+        let anchor = self.selection_anchor?;
+        return Some((anchor.min(self.index), anchor.max(self.index)));
+    }
+
+    // moves the cursor up or down and extends the selection of lines
+    fn select_vertical(&mut self, dir: Direction) -> io::Result<()> {
+        if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.index);
+        }
+        self.move_cursor_vertical(dir)?;
+        self.render_selection()?;
+        return Ok(());
+    }
+
+    fn render_selection(&mut self) -> io::Result<()> {
+        self.clear_highlight();
+        if let Some((start, end)) = self.selected_rows() {
+            let style = Theme::current().selection;
+            for line in &mut self.lines[(start - 1)..end] {
+                line.set_highlight_with(Some((0, line.len())), style);
+            }
+        }
+        self.render_all()?;
+        return Ok(());
+    }
+
+    // returns true if there was a selection
+    fn clear_selection(&mut self) -> io::Result<bool> {
+        if self.selection_anchor.take().is_none() {
+            return Ok(false);
+        }
+        self.clear_highlight();
+        self.render_all()?;
+        return Ok(true);
+    }
+
+    // indents or outdents the selected lines by one level as
+    // one event, or the current line without selection.
+    // blank lines are not indented.
+    fn indent_lines(&mut self, is_indenting: bool) -> io::Result<()> {
+        let (start, end) = self.selected_rows().unwrap_or((self.index, self.index));
+        let unit = self.indent_style().unit();
+        let texts: Vec<String> = self.lines[(start - 1)..end]
+            .iter()
+            .map(|line| match is_indenting {
+                true if line.content().trim().is_empty() => String::new(),
+                true => unit.clone(),
+                false => indent::outdent_prefix(line.content(), self.indent_width()).to_owned(),
+            })
+            .collect();
+        if texts.iter().all(|text| text.is_empty()) {
+            return Ok(());
+        }
+
+        let op = match is_indenting {
+            true => EditorOperation::IndentLines(start, texts.clone()),
+            false => EditorOperation::OutdentLines(start, texts.clone()),
+        };
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.append_event(op, |e| e.change_indents(start, &texts, is_indenting))?;
+        if self.selection_anchor.is_some() {
+            self.render_selection()?;
+        }
+        return Ok(());
+    }
+
+    // comments or uncomments the selected lines or current line
+    // as one event, the cursor stays at the same char.
+    fn toggle_comments(&mut self) -> io::Result<()> {
+        let (start, end) = self.selected_rows().unwrap_or((self.index, self.index));
+        let old_lines: Vec<&str> = self.lines[(start - 1)..end]
+            .iter()
+            .map(|line| line.content())
+            .collect();
+        let Some(toggled) = comment::toggle_comments(&old_lines, self.language()) else {
+            let text = "No comment token for this file type";
+            self.dashboard.set_message(EditorMessage::warning(text))?;
+            return Ok(());
+        };
+        let from = old_lines.join("\n");
+        let to = toggled
+            .iter()
+            .map(|line| line.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if from == to {
+            return Ok(());
+        }
+
+        let mut pos = self.cursor_pos()?;
+        let line = &toggled[pos.row - start];
+        let index = pos.col - 1;
+        if index >= line.index {
+            pos.col = (index as isize + line.shift).max(line.index as isize) as usize + 1;
+        }
+        let block_start = EditorCursorPos { row: start, col: 1 };
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            e.jump_to(block_start)?;
+            e.append_event(EditorOperation::Replace(from.clone(), to.clone()), |e| {
+                e.replace(&from, &to)
+            })?;
+            e.jump_to(pos)
+        })?;
+        if self.selection_anchor.is_some() {
+            self.render_selection()?;
+        }
+        return Ok(());
+    }
+
+    // the rows of selected lines, or of the current line
+    #[inline]
+    fn target_rows(&self) -> (usize, usize) {
+        return self.selected_rows().unwrap_or((self.index, self.index));
+    }
+
+    fn target_texts(&self, start: usize, end: usize) -> Vec<String> {
+        return self.lines[(start - 1)..end]
+            .iter()
+            .map(|line| line.content().to_owned())
+            .collect();
+    }
+
+    // inserts a copy of the selected lines or the current line,
+    // the cursor and the selection move onto the copy.
+    fn duplicate_lines(&mut self) -> io::Result<()> {
+        let (start, end) = self.target_rows();
+        let texts = self.target_texts(start, end);
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.append_event(EditorOperation::InsertLines(start, texts.clone()), |e| {
+            e.insert_lines(start, &texts)
+        })?;
+        if let Some(anchor) = self.selection_anchor.as_mut() {
+            *anchor += texts.len();
+            self.render_selection()?;
+        }
+        return Ok(());
+    }
+
+    // moves the selected lines or the current line over the
+    // line above or below.
+    fn move_target_lines(&mut self, dir: Direction) -> io::Result<()> {
+        let (start, end) = self.target_rows();
+        let is_at_edge = match dir {
+            Direction::Up => start == 1,
+            _ => end == self.lines.len(),
+        };
+        if is_at_edge {
+            return Ok(());
+        }
+        let count = end - start + 1;
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.append_event(EditorOperation::MoveLines(start, count, dir), |e| {
+            e.move_lines(start, count, dir)
+        })?;
+        if let Some(anchor) = self.selection_anchor.as_mut() {
+            *anchor = match dir {
+                Direction::Up => *anchor - 1,
+                _ => *anchor + 1,
+            };
+            self.render_selection()?;
+        }
+        return Ok(());
+    }
+
+    // deletes the selected lines or the current line entirely
+    fn delete_target_lines(&mut self) -> io::Result<()> {
+        let (start, end) = self.target_rows();
+        let texts = self.target_texts(start, end);
+        let mut pos = self.cursor_pos()?;
+        self.clear_selection()?;
+
+        // the editor keeps at least one line
+        if texts.len() == self.lines.len() {
+            let from = texts.join("\n");
+            if from.is_empty() {
+                return Ok(());
+            }
+            self.dashboard.set_state(EditorState::Modified)?;
+            return self.transaction(|e| {
+                e.jump_to(EditorCursorPos { row: 1, col: 1 })?;
+                e.append_event(EditorOperation::Replace(from.clone(), String::new()), |e| {
+                    e.replace(&from, "")
+                })
+            });
+        }
+        pos.row = start.min(self.lines.len() - texts.len());
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            e.append_event(EditorOperation::RemoveLines(start, texts.clone()), |e| {
+                e.remove_lines(start, texts.len())
+            })?;
+            pos.col = pos.col.min(e.lines[pos.row - 1].len() + 1);
+            e.jump_to(pos)
+        })?;
+        return Ok(());
+    }
+
+    // joins the selected lines, or the current line and the next one,
+    // with the leading whitespace of the joined lines replaced by a space.
+    fn join_lines(&mut self) -> io::Result<()> {
+        let (start, mut end) = self.target_rows();
+        if start == end {
+            end += 1;
+        }
+        if end > self.lines.len() {
+            return Ok(());
+        }
+        let texts = self.target_texts(start, end);
+        let mut joined = texts[0].clone();
+        for text in &texts[1..] {
+            let text = text.trim_start();
+            let needs_space =
+                !text.is_empty() && !joined.is_empty() && !joined.ends_with(char::is_whitespace);
+            if needs_space {
+                joined.push(' ');
+            }
+            joined.push_str(text);
+        }
+        let from = texts.join("\n");
+        let joint = EditorCursorPos {
+            row: start,
+            col: texts[0].len() + 1,
+        };
+        self.clear_selection()?;
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            e.jump_to(EditorCursorPos { row: start, col: 1 })?;
+            e.append_event(
+                EditorOperation::Replace(from.clone(), joined.clone()),
+                |e| e.replace(&from, &joined),
+            )?;
+            e.jump_to(joint)
+        })?;
+        return Ok(());
+    }
+
+    // inserts a blank line above or below the current line without
+    // splitting it, the new line keeps the indent of current line.
+    fn insert_blank_line(&mut self, dir: Direction) -> io::Result<()> {
+        let indent = match self.config.auto_indent {
+            true => indent::leading_whitespace(self.lines[self.index - 1].content()).to_owned(),
+            false => String::new(),
+        };
+        let row = match dir {
+            Direction::Up => self.index,
+            _ => self.index + 1,
+        };
+        let texts = vec![indent.clone()];
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            e.append_event(EditorOperation::InsertLines(row, texts.clone()), |e| {
+                e.insert_lines(row, &texts)
+            })?;
+            e.jump_to(EditorCursorPos {
+                row,
+                col: indent.len() + 1,
+            })
+        })?;
+        return Ok(());
+    }
+
+    // inserts or removes `texts` at the start of lines from `row`,
+    // the cursor stays at the same char.
+    fn change_indents(&mut self, row: usize, texts: &[String], is_indenting: bool) -> io::Result<()> {
+        // highlight ranges are stale after the change
+        self.clear_highlight();
+        let mut pos = self.cursor_pos()?;
+        for (i, text) in texts.iter().enumerate() {
+            let line = &mut self.lines[row - 1 + i];
+            let content = match is_indenting {
+                true => format!("{}{}", text, line.content()),
+                false => line.content()[text.len()..].to_owned(),
+            };
+            line.set_content(&content);
+            if row + i == pos.row {
+                pos.col = match is_indenting {
+                    true => pos.col + text.len(),
+                    false => pos.col.saturating_sub(text.len()).max(1),
+                };
+            }
+        }
+        self.jump_to(pos)?;
+        return Ok(());
+    }
+
+    // inserts `texts` as whole lines before `row`,
+    // the cursor stays at the same char.
+    fn insert_lines(&mut self, row: usize, texts: &[String]) -> io::Result<()> {
+        self.clear_highlight();
+        let mut pos = self.cursor_pos()?;
+        self.lines[self.index - 1].disable()?;
+
+        let label_width = self.label_width_with(self.lines.len() + texts.len());
+        let new_lines = texts.iter().map(|text| {
+            let mut new_line = EditorLine::new(label_width, false);
+            new_line.push_str(text);
+            new_line
+        });
+        self.lines.splice((row - 1)..(row - 1), new_lines);
+
+        if pos.row >= row {
+            pos.row += texts.len();
+        }
+        self.jump_to(pos)?;
+        return Ok(());
+    }
+
+    // removes `count` lines from `row`, the cursor on removed
+    // lines moves to the line taking their place.
+    fn remove_lines(&mut self, row: usize, count: usize) -> io::Result<()> {
+        self.clear_highlight();
+        let mut pos = self.cursor_pos()?;
+        self.lines[self.index - 1].disable()?;
+        self.lines.drain((row - 1)..(row - 1 + count));
+
+        if pos.row >= row + count {
+            pos.row -= count;
+        } else if pos.row >= row {
+            pos.row = row.min(self.lines.len());
+        }
+        self.jump_to(pos)?;
+        return Ok(());
+    }
+
+    // moves `count` lines from `row` up or down by one line,
+    // the cursor moves along with the line it is on.
+    fn move_lines(&mut self, row: usize, count: usize, dir: Direction) -> io::Result<()> {
+        self.clear_highlight();
+        let mut pos = self.cursor_pos()?;
+        self.lines[self.index - 1].disable()?;
+
+        let (start, end) = (row - 1, row - 1 + count);
+        match dir {
+            Direction::Up => {
+                self.lines[(start - 1)..end].rotate_left(1);
+                if (row..(row + count)).contains(&pos.row) {
+                    pos.row -= 1;
+                } else if pos.row == row - 1 {
+                    pos.row += count;
+                }
+            }
+            _ => {
+                self.lines[start..=end].rotate_right(1);
+                if (row..(row + count)).contains(&pos.row) {
+                    pos.row += 1;
+                } else if pos.row == row + count {
+                    pos.row -= count;
+                }
+            }
+        }
+        self.jump_to(pos)?;
+        return Ok(());
+    }
+
+    // inserts a typed char, a closing bracket typed on a
+    // blank line removes one indent level before it.
+    fn type_char(&mut self, ch: char) -> io::Result<()> {
+        if self.config.auto_pairs && self.type_pair(ch)? {
+            return Ok(());
+        }
+        let current_line = &self.lines[self.index - 1];
+        let prefix = &current_line.content()[..current_line.cursor_pos()?];
+        let is_outdenting = self.config.auto_indent
+            && indent::is_closing_bracket(ch)
+            && !prefix.is_empty()
+            && current_line.content().trim().is_empty();
+        if !is_outdenting {
+            return self.append_event(EditorOperation::InsertChar(ch), |e| e.insert_char(ch));
+        }
+
+        let outdent_len = indent::outdent_len(prefix, self.indent_width());
+        let removed = prefix[(prefix.len() - outdent_len)..].to_owned();
+        self.transaction(|e| {
+            e.delete_text(&removed)?;
+            e.append_event(EditorOperation::InsertChar(ch), |e| e.insert_char(ch))
+        })?;
+        return Ok(());
+    }
+
+    // inserts the closer with an opener, or steps over the
+    // closer inserted with its opener.
+    // quotes after word chars are not paired, e.g. `don't`.
+    // returns false if `ch` is not handled.
+    fn type_pair(&mut self, ch: char) -> io::Result<bool> {
+        let current_line = &mut self.lines[self.index - 1];
+        let (before, after) = current_line.chars_around_cursor()?;
+        if self.auto_closed > 0 && after == Some(ch) && pairs::is_closer(ch) {
+            current_line.move_cursor_horizontal(Direction::Right)?;
+            self.auto_closed -= 1;
+            return Ok(true);
+        }
+
+        let Some(close) = pairs::closer_of(ch) else {
+            return Ok(false);
+        };
+        if pairs::is_quote(ch) && before.is_some_and(pairs::is_word_char) {
+            return Ok(false);
+        }
+        self.append_event(EditorOperation::InsertPair(ch, close), |e| {
+            e.insert_pair(ch, close)
+        })?;
+        self.auto_closed += 1;
+        return Ok(true);
+    }
+
+    fn paste(&mut self, text: &str) -> io::Result<()> {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        if self.components.use_line_component || self.components.use_screen_component {
+            // components only accept single line text
+            let current_state = self.dashboard.state();
+            for ch in text.chars().filter(|ch| ch.is_ascii() && !ch.is_ascii_control()) {
+                let key = KeyEvent::from(KeyCode::Char(ch));
+                self.components.resolve(current_state, key)?;
+            }
+            return Ok(());
+        }
+
+        self.clear_selection()?;
+        self.auto_closed = 0;
+        self.dashboard.set_state(EditorState::Modified)?;
+        self.transaction(|e| {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    e.append_event(EditorOperation::InsertLine, |e| e.insert_line())?;
+                }
+                let line = line
+                    .chars()
+                    .filter(|ch| *ch == '\t' || (ch.is_ascii() && !ch.is_ascii_control()))
+                    .collect::<String>();
+                e.insert_text(&line)?;
+            }
+            return Ok(());
+        })?;
+        self.dashboard_cursor_pos_refresh()?;
+        return Ok(());
+    }
+
+    // caches the history of current file for next opening.
+    fn save_history(&self) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let path = Path::new(self.components.file_saver.path());
+        // failing to cache history should not break editing
+        let _ = undo_cache::save(&self.history, path, &self.content_with("\n"));
+    }
+
+    // writes the modified buffer into swap file,
+    // so that it can be recovered after crashing.
+    fn autosave(&mut self) {
+        let is_modified = self.dashboard.saved_state() == EditorState::Modified;
+        if self.lines.is_empty()
+            || !is_modified
+            || !self.config.autosave
+            || self.last_autosave_time.elapsed() < self.config.autosave_interval
+        {
+            return;
+        }
+        self.last_autosave_time = Instant::now();
+
+        let content = self.content_with("\n");
+        let hash = content_hash(content.as_bytes());
+        if self.swap_hash == Some(hash) {
+            return;
+        }
+        let path = Path::new(self.components.file_saver.path());
+        if swap_file::write(path, &content).is_ok() {
+            self.swap_hash = Some(hash);
+        }
+    }
+
+    // removes the swap file written by current process.
+    fn remove_swap(&mut self) {
+        if self.swap_hash.take().is_some() {
+            swap_file::remove(Path::new(self.components.file_saver.path()));
+        }
+    }
+
+    // opens the swap recoverer if a swap file of current file is found.
+    fn offer_recovery(&mut self) -> io::Result<()> {
+        let is_using_component =
+            self.components.use_line_component || self.components.use_screen_component;
+        if self.components.swap_recoverer.has_swap() && !is_using_component {
+            self.toggle_state(EditorState::Recovering)?;
+        }
+        return Ok(());
+    }
+
+    // groups all the events appended in `callback` into one undo step.
+    fn transaction(
+        &mut self,
+        callback: impl FnOnce(&mut Editor) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.history.begin(self.cursor_pos()?);
+        let result = callback(self);
+        self.history.commit(self.cursor_pos()?);
+        return result;
+    }
+
     fn undo(&mut self) -> io::Result<()> {
         if let Some(ev) = self.history.undo() {
-            let target_pos = ev.pos_after;
-            let target_op = ev.op.rev();
-
-            self.jump_to(target_pos)?;
-            self.exec_operation(target_op)?;
+            let ev = ev.clone();
+            self.undo_event(ev)?;
         }
         return Ok(());
     }
     fn redo(&mut self) -> io::Result<()> {
         if let Some(ev) = self.history.redo() {
-            let target_pos = ev.pos_before;
-            let target_op = ev.op.clone();
+            let ev = ev.clone();
+            self.redo_event(ev)?;
+        }
+        return Ok(());
+    }
 
-            self.jump_to(target_pos)?;
-            self.exec_operation(target_op)?;
+    fn undo_event(&mut self, ev: EditorEvent) -> io::Result<()> {
+        let is_group = matches!(ev.op, EditorOperation::Group(_));
+        self.jump_to(ev.pos_after)?;
+        self.exec_operation(ev.op.rev())?;
+        if is_group {
+            self.jump_to(ev.pos_before)?;
+        }
+        return Ok(());
+    }
+    fn redo_event(&mut self, ev: EditorEvent) -> io::Result<()> {
+        let is_group = matches!(ev.op, EditorOperation::Group(_));
+        self.jump_to(ev.pos_before)?;
+        self.exec_operation(ev.op)?;
+        if is_group {
+            self.jump_to(ev.pos_after)?;
+        }
+        return Ok(());
+    }
+
+    // undoes and redoes along the undo tree
+    // to the state with sequence number `seq`.
+    fn travel_to(&mut self, seq: usize) -> io::Result<()> {
+        loop {
+            let ev = match self.history.step_to(seq) {
+                Some(HistoryStep::Undo(ev)) => ev.clone(),
+                Some(HistoryStep::Redo(ev)) => {
+                    let ev = ev.clone();
+                    self.redo_event(ev)?;
+                    continue;
+                }
+                None => break,
+            };
+            self.undo_event(ev)?;
         }
         return Ok(());
     }
@@ -367,45 +1368,40 @@ impl Editor {
     }
 
     fn check_cursor_pos(&self, pos: EditorCursorPos) -> bool {
-        let EditorCursorPos { row, col } = pos;
-        let is_row_overflow = row > self.lines.len();
-        let is_col_overflow = if is_row_overflow {
-            true
-        } else {
-            let target_line = &self.lines[row - 1];
-            col == 0 || col > target_line.len() + 1
-        };
-        return !is_row_overflow && !is_col_overflow;
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.content()).collect();
+        return pos.is_within(&lines);
     }
 
     fn jump_to(&mut self, target_pos: EditorCursorPos) -> io::Result<()> {
-        if target_pos == self.cursor_pos()? {
-            return Ok(());
-        }
-
-        // move to target row
+        let line_count = self.lines.len();
+        let visible_area_height = self.visible_area_height();
         let target_row = target_pos.row;
-        let (dir, diff) = if target_row > self.index {
-            (Direction::Down, target_row - self.index)
-        } else {
-            (Direction::Up, self.index - target_row)
-        };
-        for _ in 0..diff {
-            self.move_cursor_vertical(dir)?;
+
+        if let Some(current_line) = self.lines.get_mut(self.index - 1) {
+            current_line.disable()?;
         }
-        // is not first and last line
-        if self.index != 1 && self.index != self.lines.len() {
-            self.move_cursor_vertical(dir)?;
-            self.move_cursor_vertical(dir.rev())?;
+
+        // scroll to make the target row visible
+        if target_row <= self.overflow_top {
+            self.overflow_top = target_row - 1;
+        } else if target_row > self.overflow_top + visible_area_height {
+            self.overflow_top = target_row - visible_area_height;
         }
+        self.overflow_top = self
+            .overflow_top
+            .min(line_count.saturating_sub(visible_area_height));
+        self.overflow_bottom = line_count.saturating_sub(self.overflow_top + visible_area_height);
+
+        // move to target row
+        self.index = target_row;
+        Cursor::move_to_row(target_row - self.overflow_top)?;
 
         // move to target col
         let label_width = self.label_width();
         let target_line = &mut self.lines[target_row - 1];
         target_line.move_cursor_to_start(label_width)?;
-        for _ in 0..(target_pos.col - 1) {
-            target_line.move_cursor_horizontal(Direction::Right)?;
-        }
+        target_line.move_cursor_to(target_pos.col - 1)?;
+        target_line.active()?;
         self.render_all()?;
         return Ok(());
     }
@@ -428,6 +1424,7 @@ impl Editor {
             EditorState::Positioning => self.components.positioner.open()?,
             EditorState::Finding => self.components.finder.open()?,
             EditorState::Replacing => self.components.replacer.open()?,
+            EditorState::SearchingFiles => self.components.file_searcher.open()?,
             _ => unreachable!(),
         }
         return Ok(());
@@ -440,37 +1437,143 @@ impl Editor {
         return Ok(());
     }
 
-    fn callbacks_resolve(&mut self, key: KeyEvent) -> io::Result<()> {
+    fn callbacks_resolve(&mut self, input: EditorInput) -> io::Result<()> {
         match self.dashboard.state() {
-            EditorState::Saving if FileSaver::is_save_callback_key(key) => {
+            EditorState::Saving if FileSaver::is_save_callback_key(input) => {
+                if !self.components.file_saver.take_saved() {
+                    return Ok(());
+                }
+                self.toggle_state(EditorState::Saving)?;
                 self.dashboard.set_state(EditorState::Saved)?;
+                self.save_history();
+                self.remove_swap();
             }
-            EditorState::Opening if FileOpener::is_open_file_callback_key(key) => {
+            EditorState::Opening if FileOpener::is_open_file_callback_key(input) => {
+                let Some(path) = self.components.file_opener.take_file_path() else {
+                    return Ok(());
+                };
                 self.toggle_state(EditorState::Opening)?;
-                let path = self.components.file_opener.get_file_path().to_owned();
-                self.read_file(&path)?;
+                if let Err(err) = self.read_file(&path) {
+                    let text = format!("Cannot open {}: {}", path, err);
+                    self.dashboard.set_message(EditorMessage::error(text))?;
+                    return Ok(());
+                }
+                self.render_all()?;
+                self.reset_cursor_pos()?;
+                self.offer_recovery()?;
+            }
+            EditorState::Recovering if SwapRecoverer::is_action_key(input) => {
+                let Some((action, swap)) = self.components.swap_recoverer.take_action() else {
+                    return Ok(());
+                };
+                self.toggle_state(EditorState::Recovering)?;
+                match action {
+                    RecoverAction::Recover => {
+                        self.set_lines(&swap.content);
+                        if self.lines.is_empty() {
+                            let label_width = self.label_width();
+                            self.lines.push(EditorLine::new(label_width, false));
+                        }
+                        self.history = EditorHistory::new();
+                        self.history.set_max_size(self.config.history_size);
+                        self.render_all()?;
+                        self.reset_cursor_pos()?;
+                        self.dashboard.set_state(EditorState::Modified)?;
+                    }
+                    RecoverAction::Delete => {
+                        let _ = fs::remove_file(&swap.swap_path);
+                    }
+                }
+                self.dashboard_cursor_pos_refresh()?;
+            }
+            EditorState::SearchingFiles if FileSearcher::is_open_file_callback_key(input) => {
+                let Some(target) = self.components.file_searcher.take_target() else {
+                    return Ok(());
+                };
+                self.toggle_state(EditorState::SearchingFiles)?;
+                let path = target.path.to_string_lossy().to_string();
+                if let Err(err) = self.read_file(&path) {
+                    let text = format!("Cannot open {}: {}", path, err);
+                    self.dashboard.set_message(EditorMessage::error(text))?;
+                    return Ok(());
+                }
                 self.render_all()?;
                 self.reset_cursor_pos()?;
+                if self.check_cursor_pos(target.pos) {
+                    self.jump_to(target.pos)?;
+                }
+                self.dashboard_cursor_pos_refresh()?;
+                self.offer_recovery()?;
             }
-            EditorState::Positioning if Positioner::is_positioning_key(key) => {
+            EditorState::SearchingFiles if FileSearcher::is_write_callback_key(input) => {
+                let changed_files = self.components.file_searcher.take_changed_files();
+                let current_path = Path::new(self.components.file_saver.path()).canonicalize();
+                let is_current_changed = changed_files
+                    .iter()
+                    .any(|p| p.canonicalize().ok() == current_path.as_ref().ok().cloned());
+
+                // reload the current file if it has no unsaved change
+                if is_current_changed && self.dashboard.saved_state() == EditorState::Saved {
+                    let path = self.components.file_saver.path().to_owned();
+                    self.component_exec(|e| {
+                        if let Err(err) = e.read_file(&path) {
+                            let text = format!("Cannot reload {}: {}", path, err);
+                            e.dashboard.set_message(EditorMessage::error(text))?;
+                            return Ok(());
+                        }
+                        e.reset_cursor_pos()?;
+                        e.render_all()?;
+                        return Ok(());
+                    })?;
+                }
+            }
+            EditorState::ViewingHistory if HistoryViewer::is_travel_key(input) => {
+                let Some(target) = self.components.history_viewer.take_target() else {
+                    return Ok(());
+                };
+                self.toggle_state(EditorState::ViewingHistory)?;
+                let seq = match target {
+                    HistoryTarget::State(seq) => seq,
+                    HistoryTarget::Earlier(offset) => self.history.seq_from_now(offset, true),
+                    HistoryTarget::Later(offset) => self.history.seq_from_now(offset, false),
+                };
+                if !self.history.contains(seq) {
+                    let text = format!("State #{} does not exist", seq);
+                    self.dashboard.set_message(EditorMessage::warning(text))?;
+                    return Ok(());
+                }
+                self.travel_to(seq)?;
+                self.dashboard_cursor_pos_refresh()?;
+            }
+            EditorState::Positioning if Positioner::is_positioning_key(input) => {
                 self.toggle_state(EditorState::Positioning)?;
 
                 let target_pos = self.components.positioner.get_target();
                 if self.check_cursor_pos(target_pos) {
                     self.jump_to(target_pos)?;
                     self.dashboard.set_cursor_pos(target_pos)?;
+                } else {
+                    let text = format!("{} is out of range", target_pos);
+                    self.dashboard.set_message(EditorMessage::warning(text))?;
                 }
             }
             EditorState::Finding => {
-                let option_target_pos = if Finder::is_finding_key(key) {
+                let option_target_pos = if Finder::is_finding_key(input) {
                     if self.components.finder.is_empty() {
                         let target_text = self.components.finder.content();
-                        if let Some(pos_list) = self.search(target_text) {
-                            self.components.finder.set_matches(pos_list);
+                        let option_pos_list =
+                            SearchPattern::new(target_text, false).and_then(|p| self.search(&p));
+                        match option_pos_list {
+                            Some(pos_list) => self.components.finder.set_matches(pos_list),
+                            None if !target_text.is_empty() => {
+                                let text = format!("No matches for {}", target_text);
+                                self.dashboard.set_message(EditorMessage::warning(text))?;
+                            }
+                            None => {}
                         }
                     }
                     self.components.finder.next()
-                } else if Finder::is_reverse_finding_key(key) {
+                } else if Finder::is_reverse_finding_key(input) {
                     self.components.finder.previous()
                 } else {
                     None
@@ -482,98 +1585,51 @@ impl Editor {
                 }
             }
             EditorState::Replacing => {
-                fn replace_pos_processor(
-                    last_event: &EditorEvent,
-                    current_pos: EditorCursorPos,
-                    next_pos: &mut EditorCursorPos,
-                ) {
-                    let EditorEvent {
-                        op: EditorOperation::Replace(from, to),
-                        pos_before,
-                        ..
-                    } = last_event
-                    else {
-                        return;
+                let replacer = &self.components.replacer;
+                if replacer.is_search_key(input) {
+                    let search_text = replacer.search_text().to_owned();
+                    let Some(pattern) = replacer.search_pattern() else {
+                        if !search_text.is_empty() {
+                            let text = format!("Invalid pattern: {}", search_text);
+                            self.dashboard.set_message(EditorMessage::error(text))?;
+                        }
+                        return Ok(());
                     };
-
-                    if pos_before.row == next_pos.row {
-                        let text_diff = from.len().abs_diff(to.len());
-                        let col_diff = next_pos.col - current_pos.col;
-                        next_pos.col = pos_before.col + col_diff;
-                        if from.len() > to.len() {
-                            next_pos.col -= text_diff;
-                        } else {
-                            next_pos.col += text_diff;
+                    match self.find_match_from(&pattern, 0) {
+                        Some((first_pos, _)) => {
+                            let replacer = &mut self.components.replacer;
+                            replacer.search_handler(pattern, first_pos)?;
+                            self.component_exec(|e| e.focus_match(first_pos))?;
                         }
-                    }
-                }
-
-                if self.components.replacer.is_search_key(key) {
-                    let target_content = self.components.replacer.search_text();
-                    if let Some(pos_list) = self.search(target_content) {
-                        let replacer = &mut self.components.replacer;
-                        replacer.search_handler(pos_list)?;
-
-                        let first_target_pos = *replacer.first().unwrap();
-                        self.component_exec(|e| e.jump_to(first_target_pos))?;
-                    }
-                } else if self.components.replacer.is_next_key(key) {
-                    // jump to next position
-                    let next_pos = self.components.replacer.next();
-                    if let Some(pos) = next_pos {
-                        let pos = *pos;
-                        self.component_exec(|e| e.jump_to(pos))?;
-                    }
-                } else if self.components.replacer.is_replace_one_key(key) {
-                    self.component_exec(|e| {
-                        let replacer = &mut e.components.replacer;
-                        let current_pos = *replacer.current();
-
-                        if let Some(mut next_pos) = replacer.next().cloned() {
-                            if let Some(ev) = e.history.previous_event() {
-                                replace_pos_processor(ev, current_pos, &mut next_pos);
-                            }
-
-                            let replace_op = EditorOperation::Replace(
-                                replacer.search_text().to_owned(),
-                                replacer.replace_text().to_owned(),
-                            );
-                            let replace_count = replacer.search_text().len();
-                            let replace_text = &replacer.replace_text().to_owned();
-                            replacer.replace_handler();
-
-                            e.jump_to(next_pos)?;
-                            e.append_event(replace_op, |e| e.replace(replace_count, replace_text))?;
+                        None => {
+                            let text = format!("No matches for {}", search_text);
+                            self.dashboard.set_message(EditorMessage::warning(text))?;
                         }
-                        return Ok(());
-                    })?;
-                } else if self.components.replacer.is_replace_all_key(key) {
+                    }
+                } else if replacer.is_confirm_key(input) {
+                    self.components.replacer.confirm_handler()?;
+                } else if replacer.is_next_key(input) {
+                    self.component_exec(|e| e.replace_step(false))?;
+                } else if replacer.is_replace_one_key(input) {
+                    self.components.replacer.replace_handler();
+                    self.component_exec(|e| e.replace_step(true))?;
+                } else if replacer.is_replace_all_key(input) {
                     // close replacer
                     self.toggle_state(EditorState::Replacing)?;
+                    self.components.replacer.replace_handler();
+                    self.replace_all_remaining()?;
+                    return Ok(());
+                } else if replacer.is_undo_key(input) {
+                    self.component_exec(|e| e.undo_replacement())?;
+                } else if replacer.is_quit_key(input) {
+                    self.toggle_state(EditorState::Replacing)?;
+                    return Ok(());
+                }
 
-                    let replacer = &mut self.components.replacer;
-                    let replace_count = replacer.search_text().len();
-                    let replace_text = &replacer.replace_text().to_owned();
-                    let replace_op = EditorOperation::Replace(
-                        replacer.search_text().to_owned(),
-                        replacer.replace_text().to_owned(),
-                    );
-                    replacer.replace_handler();
-
-                    let mut current_pos = *replacer.current();
-
-                    while let Some(mut next_pos) = self.components.replacer.next().cloned() {
-                        if let Some(ev) = self.history.previous_event() {
-                            replace_pos_processor(ev, current_pos, &mut next_pos);
-                        }
-
-                        self.jump_to(next_pos)?;
-                        self.append_event(replace_op.clone(), |e| {
-                            e.replace(replace_count, replace_text)
-                        })?;
-
-                        current_pos = *self.components.replacer.current();
-                    }
+                // close replacer when there is no more matching
+                let replacer = &self.components.replacer;
+                if replacer.pattern().is_some() && replacer.current().is_none() {
+                    self.toggle_state(EditorState::Replacing)?;
                 }
             }
             _ => {}
@@ -584,27 +1640,44 @@ impl Editor {
 
 // Non-editing methods
 impl Editor {
-    pub fn new() -> Self {
+    pub fn new(config: EditorConfig) -> Self {
+        TextArea::set_tab_width(config.tab_width);
+        let mut history = EditorHistory::new();
+        history.set_max_size(config.history_size);
         Self {
             lines: vec![],
             index: 1,
 
             overflow_top: 0,
             overflow_bottom: 0,
+            selection_anchor: None,
+            indent_style: match config.soft_tabs {
+                true => IndentStyle::Spaces(config.tab_width),
+                false => IndentStyle::Tabs,
+            },
+            file_properties: FileProperties::default(),
+            matched_bracket: None,
+            auto_closed: 0,
 
             components: EditorComponentManager::new(),
-            history: EditorHistory::new(),
+            history,
             dashboard: EditorDashboard::new(),
+
+            last_autosave_time: Instant::now(),
+            swap_hash: None,
+
+            config,
+            pending_keys: vec![],
         }
     }
 
     pub fn init(&mut self) -> io::Result<()> {
-        execute!(io::stdout(), EnterAlternateScreen)?;
-        enable_raw_mode()?;
+        Terminal::setup()?;
         Cursor::move_to_left_top()?;
 
         EditorInit::display_title();
         EditorInit::display_border()?;
+        self.dashboard.set_indent_style(self.indent_style);
         self.dashboard.render()?;
 
         // lines.is_empty() == true -> no file reading
@@ -622,69 +1695,114 @@ impl Editor {
             .move_cursor_to_start(label_width)?;
 
         self.render_all()?;
+        self.offer_recovery()?;
         Terminal::flush()?;
         return Ok(());
     }
 
     pub fn close(&self) -> io::Result<()> {
-        disable_raw_mode()?;
-        execute!(io::stdout(), LeaveAlternateScreen)?;
-        return Ok(());
+        Terminal::restore()
     }
 
-    #[inline]
-    pub fn set_accent_color(color: &str) {
-        EditorColor::set_accent_color(color);
+    // writes current text into a recovery file when exiting
+    // because of errors, returns None if there is no text.
+    pub fn dump_buffer(&self) -> io::Result<Option<PathBuf>> {
+        if self.lines.is_empty() {
+            return Ok(None);
+        }
+        let path = Path::new(self.components.file_saver.path());
+        let dump_path = swap_file::dump(path, &self.content_with("\n"))?;
+        return Ok(Some(dump_path));
+    }
+
+    // shows the first invalid entry of config file in dashboard.
+    pub fn report_config_errors(&mut self, errors: &[ConfigError]) -> io::Result<()> {
+        let Some(first) = errors.first() else {
+            return Ok(());
+        };
+        let text = match errors.len() {
+            1 => first.to_string(),
+            n => format!("{} (and {} more errors)", first, n - 1),
+        };
+        self.dashboard.set_message(EditorMessage::error(text))?;
+        Terminal::flush()?;
+        return Ok(());
     }
 
     pub fn read_file(&mut self, path: &str) -> io::Result<()> {
+        let properties = FileProperties::find(Path::new(path));
+        // keep current file if the reading fails
+        let content = match Path::new(path).exists() {
+            true => match properties.charset {
+                Some(charset) => Some(charset.decode(&fs::read(path)?)?),
+                None => Some(fs::read_to_string(path)?),
+            },
+            false => None,
+        };
+        self.save_history();
+        self.remove_swap();
+        self.file_properties = properties;
+        self.apply_file_properties(content.as_deref());
         self.components.file_saver.set_path(path);
-        if !Path::new(path).exists() {
+        let swap = swap_file::find(Path::new(path));
+        let Some(content) = content else {
+            self.components.swap_recoverer.set_swap(swap, String::new());
             return Ok(());
-        }
-
-        let file_read_res = fs::read_to_string(path);
-        match file_read_res {
-            Ok(content) => {
-                let file_lines = content.lines();
-                let line_count = file_lines.clone().count();
-                let visible_area_height = self.visible_area_height();
-                let label_width = self.label_width_with(line_count);
+        };
 
-                // set `overflow_bottom`
-                if line_count > visible_area_height {
-                    self.overflow_bottom = line_count - visible_area_height;
-                }
+        self.set_lines(&content);
+        // restore the history of last editing,
+        // events of the previous file is not available
+        self.history =
+            undo_cache::load(Path::new(path), &content).unwrap_or_else(EditorHistory::new);
+        self.history.set_max_size(self.config.history_size);
+        self.components.swap_recoverer.set_swap(swap, content);
+        return Ok(());
+    }
 
-                self.lines = file_lines
-                    .map(|l| {
-                        let mut new_line = EditorLine::new(label_width, false);
-                        new_line.push_str(l);
-                        new_line
-                    })
-                    .collect();
-            }
-            Err(_) => {
-                self.close()?;
-                panic!("File reading failed!")
-            }
+    // sets the tab width, indent style and the rules of saving of
+    // current file. the indent style set by `.editorconfig` takes
+    // precedence over the style detected from `content`.
+    fn apply_file_properties(&mut self, content: Option<&str>) {
+        let tab_width = self.tab_width();
+        TextArea::set_tab_width(tab_width);
+        let default_style = match self.config.soft_tabs {
+            true => IndentStyle::Spaces(tab_width),
+            false => IndentStyle::Tabs,
         };
-        return Ok(());
+        self.indent_style = self
+            .file_properties
+            .indent_style(tab_width)
+            .or_else(|| indent::detect(content?))
+            .unwrap_or(default_style);
+        self.dashboard.set_indent_style(self.indent_style);
+        let line_ending = self.line_ending();
+        self.components
+            .file_saver
+            .set_properties(self.file_properties.clone(), line_ending);
     }
 
-    fn search(&self, target: &str) -> Option<Vec<EditorCursorPos>> {
-        let mut result_pos_list = Vec::<EditorCursorPos>::new();
-        for (index, line) in self.lines.iter().enumerate() {
-            if let Some(pos_list) = line.find_all(target) {
-                for pos in pos_list {
-                    result_pos_list.push(EditorCursorPos {
-                        row: index + 1,
-                        col: pos + 1,
-                    })
-                }
-            }
-        }
+    fn set_lines(&mut self, content: &str) {
+        let file_lines = content.lines();
+        let line_count = file_lines.clone().count();
+        let visible_area_height = self.visible_area_height();
+        let label_width = self.label_width_with(line_count);
+
+        // set `overflow_bottom`
+        self.overflow_top = 0;
+        self.overflow_bottom = line_count.saturating_sub(visible_area_height);
+
+        self.lines = file_lines
+            .map(|l| {
+                let mut new_line = EditorLine::new(label_width, false);
+                new_line.push_str(l);
+                new_line
+            })
+            .collect();
+    }
 
+    fn search(&self, pattern: &SearchPattern) -> Option<Vec<EditorCursorPos>> {
+        let result_pos_list = pattern.find_all_pos(&self.content_with("\n"));
         if !result_pos_list.is_empty() {
             return Some(result_pos_list);
         } else {
@@ -692,26 +1810,156 @@ impl Editor {
         }
     }
 
+    #[inline]
     fn content(&self) -> String {
+        self.content_with(self.line_ending().as_str())
+    }
+
+    fn content_with(&self, line_break: &str) -> String {
         let mut buf = String::new();
         let mut iter = self.lines.iter();
         while let Some(line) = iter.next() {
             buf += line.content();
             if iter.len() > 0 {
-                buf += "\r\n";
+                buf += line_break;
             }
         }
         return buf;
     }
 
+    // returns the position of byte `offset` in content joined by `\n`.
+    fn pos_of(&self, mut offset: usize) -> EditorCursorPos {
+        for (index, line) in self.lines.iter().enumerate() {
+            if offset <= line.len() {
+                return EditorCursorPos {
+                    row: index + 1,
+                    col: offset + 1,
+                };
+            }
+            offset -= line.len() + 1;
+        }
+        let last_line = self.lines.last().unwrap();
+        return EditorCursorPos {
+            row: self.lines.len(),
+            col: last_line.len() + 1,
+        };
+    }
+
+    // returns the byte offset of `pos` in content joined by `\n`.
+    fn offset_of(&self, pos: EditorCursorPos) -> usize {
+        let lines_before = &self.lines[..(pos.row - 1)];
+        let offset = lines_before.iter().map(|l| l.len() + 1).sum::<usize>();
+        return offset + pos.col - 1;
+    }
+
     // --- --- --- --- --- ---
 
+    // returns the input for the pressed key,
+    // None if the key is a part of key sequence.
+    fn resolve_key(&mut self, key: KeyEvent) -> io::Result<Option<EditorInput>> {
+        self.pending_keys.push(KeyChord::from(key));
+        let keymap = &self.config.keymap;
+        let state = self.dashboard.state();
+        let is_typing = self.components.is_typing(state);
+        match keymap.resolve(&self.pending_keys, state, is_typing) {
+            KeyMatch::Action(action) => {
+                self.pending_keys.clear();
+                return Ok(Some(EditorInput::Action(action)));
+            }
+            KeyMatch::Pending => {
+                let text = format!("{} -", self.pending_keys_display());
+                self.dashboard.set_message(EditorMessage::info(text))?;
+                return Ok(None);
+            }
+            KeyMatch::None if self.pending_keys.len() == 1 => {
+                self.pending_keys.clear();
+                return Ok(Some(EditorInput::Key(key)));
+            }
+            KeyMatch::None => {
+                let text = format!("{} is not bound", self.pending_keys_display());
+                self.pending_keys.clear();
+                self.dashboard.set_message(EditorMessage::warning(text))?;
+                return Ok(None);
+            }
+        }
+    }
+
+    fn pending_keys_display(&self) -> String {
+        let names: Vec<String> = self.pending_keys.iter().map(|k| k.to_string()).collect();
+        return names.join(", ");
+    }
+
+    // resolves the actions working in any state.
+    fn action_resolve(&mut self, action: EditorAction) -> io::Result<()> {
+        let is_using_component =
+            self.components.use_line_component || self.components.use_screen_component;
+        match action {
+            EditorAction::Cancel => {
+                // restore to normal mode
+                let current_state = self.dashboard.state();
+                self.toggle_state(current_state)?;
+            }
+            EditorAction::Save => self.toggle_state(EditorState::Saving)?,
+            EditorAction::Open => self.toggle_state(EditorState::Opening)?,
+            EditorAction::Help => self.toggle_state(EditorState::ReadingHelpMsg)?,
+            EditorAction::GoTo => self.toggle_state(EditorState::Positioning)?,
+            EditorAction::Find => self.toggle_state(EditorState::Finding)?,
+            EditorAction::Replace => self.toggle_state(EditorState::Replacing)?,
+            EditorAction::SearchFiles => self.toggle_state(EditorState::SearchingFiles)?,
+            EditorAction::UndoTree => self.toggle_state(EditorState::ViewingHistory)?,
+
+            // editing actions only work in normal mode
+            _ if is_using_component => {}
+            EditorAction::Undo => self.undo()?,
+            EditorAction::Redo => self.redo()?,
+            EditorAction::HistoryOlder | EditorAction::HistoryNewer => {
+                // travel across branches of undo tree
+                let option_seq = match action {
+                    EditorAction::HistoryOlder => self.history.older_seq(),
+                    _ => self.history.newer_seq(),
+                };
+                if let Some(seq) = option_seq {
+                    self.travel_to(seq)?;
+                }
+            }
+            EditorAction::Retab => self.retab()?,
+            EditorAction::MatchBracket => self.jump_to_matching_bracket()?,
+            EditorAction::ToggleComment => self.toggle_comments()?,
+            EditorAction::DuplicateLines => self.duplicate_lines()?,
+            EditorAction::MoveLinesUp => self.move_target_lines(Direction::Up)?,
+            EditorAction::MoveLinesDown => self.move_target_lines(Direction::Down)?,
+            EditorAction::DeleteLines => self.delete_target_lines()?,
+            EditorAction::JoinLines => self.join_lines()?,
+            EditorAction::InsertLineAbove => self.insert_blank_line(Direction::Up)?,
+            EditorAction::InsertLineBelow => self.insert_blank_line(Direction::Down)?,
+            EditorAction::WordLeft | EditorAction::WordRight => {
+                let direction = match action {
+                    EditorAction::WordLeft => Direction::Left,
+                    _ => Direction::Right,
+                };
+                let current_line = &mut self.lines[self.index - 1];
+                current_line.jump_to_word_edge(direction)?;
+            }
+            _ => {}
+        }
+        if !self.components.use_line_component && !self.components.use_screen_component {
+            self.dashboard_cursor_pos_refresh()?;
+        }
+        return Ok(());
+    }
+
     fn toggle_state(&mut self, new_state: EditorState) -> io::Result<()> {
+        let is_using_component =
+            self.components.use_line_component || self.components.use_screen_component;
         match self.dashboard.state() {
             // set mode
-            EditorState::Saved | EditorState::Modified if !self.components.use_line_component => {
+            EditorState::Saved | EditorState::Modified if !is_using_component => {
                 Cursor::save_pos()?;
-                self.components.use_line_component = true;
+                if new_state.is_screen_component_state() {
+                    self.components.use_screen_component = true;
+                } else {
+                    self.components.use_line_component = true;
+                }
                 self.dashboard.set_state(new_state)?;
 
                 match new_state {
@@ -745,9 +1993,30 @@ impl Editor {
                         let helper = &self.components.helper;
                         helper.open()?;
                     }
+                    EditorState::SearchingFiles => {
+                        let file_searcher = &mut self.components.file_searcher;
+                        file_searcher.open()?;
+                    }
+                    EditorState::ViewingHistory => {
+                        let rows = self.history.rows();
+                        let history_viewer = &mut self.components.history_viewer;
+                        history_viewer.set_rows(rows);
+                        history_viewer.open()?;
+                    }
+                    EditorState::Recovering => {
+                        let swap_recoverer = &mut self.components.swap_recoverer;
+                        swap_recoverer.open()?;
+                    }
                     _ => unreachable!(),
                 }
             }
+            // restore to normal mode from screen component
+            s if s == new_state && self.components.use_screen_component => {
+                Cursor::restore_pos()?;
+                self.render_all()?;
+                self.dashboard.restore_state()?;
+                self.components.use_screen_component = false;
+            }
             // restore to normal mode
             s if s == new_state && self.components.use_line_component => {
                 // restore the covered line
@@ -760,10 +2029,14 @@ impl Editor {
                         let label_width = self.label_width();
                         Cursor::move_to_col(0)?;
                         Terminal::clear_after_cursor()?;
-                        print!("{}", " ".repeat(label_width).on_grey());
+                        print!("{}", Theme::current().line_label.apply(" ".repeat(label_width)));
                     }
                 }
                 Cursor::restore_pos()?;
+                if s == EditorState::Replacing {
+                    self.clear_highlight();
+                    self.render_all()?;
+                }
                 self.dashboard.restore_state()?;
                 self.components.use_line_component = false;
             }
@@ -774,56 +2047,95 @@ impl Editor {
 
     pub fn cycle(&mut self) -> io::Result<()> {
         loop {
-            let Some(key) = Terminal::get_key() else {
-                continue;
+            self.autosave();
+            if self.dashboard.expire_message()? {
+                Terminal::flush()?;
+            }
+            // wake up in time to clear the message
+            let timeout = match self.dashboard.message_remaining() {
+                Some(remaining) => remaining.min(self.config.autosave_interval),
+                None => self.config.autosave_interval,
+            };
+            let key = match Terminal::get_input(timeout) {
+                Some(TerminalInput::Key(key)) => key,
+                Some(TerminalInput::Paste(text)) => {
+                    self.paste(&text)?;
+                    Terminal::flush()?;
+                    continue;
+                }
+                None => continue,
             };
+            // messages only last until the next keystroke
+            self.dashboard.clear_message()?;
 
-            // ctrl shotcuts
-            if key.modifiers == KeyModifiers::CONTROL {
-                match key.code {
-                    KeyCode::Left | KeyCode::Right => {
-                        let current_line = &mut self.lines[self.index - 1];
-                        current_line.jump_to_word_edge(Direction::from(key.code))?;
+            let Some(input) = self.resolve_key(key)? else {
+                // waiting for the rest of key sequence
+                Terminal::flush()?;
+                continue;
+            };
+            let is_using_component =
+                self.components.use_line_component || self.components.use_screen_component;
+
+            if let EditorInput::Action(action) = input {
+                if action.state().is_none() {
+                    if action == EditorAction::Cancel && !is_using_component {
+                        // cancels the selection before exiting
+                        if self.clear_selection()? {
+                            Terminal::flush()?;
+                            continue;
+                        }
+                        break;
+                    }
+                    if !action.keeps_selection() {
+                        self.clear_selection()?;
                     }
-                    KeyCode::Char(ch) => match ch {
-                        'z' => self.undo()?,
-                        'y' => self.redo()?,
-                        's' => self.toggle_state(EditorState::Saving)?,
-                        'o' => self.toggle_state(EditorState::Opening)?,
-                        'h' => self.toggle_state(EditorState::ReadingHelpMsg)?,
-                        'g' => self.toggle_state(EditorState::Positioning)?,
-                        'f' => self.toggle_state(EditorState::Finding)?,
-                        'r' => self.toggle_state(EditorState::Replacing)?,
-                        _ => {}
-                    },
-
-                    // ignore other Ctrl shotcuts
-                    _ => {}
-                }
-
-                if !self.components.use_line_component || !self.components.use_screen_component {
+                    self.auto_closed = 0;
+                    self.action_resolve(action)?;
+                    self.refresh_bracket_match()?;
+                    Terminal::flush()?;
                     continue;
                 }
             }
 
-            if self.components.use_line_component || self.components.use_screen_component {
+            if is_using_component {
                 let current_state = self.dashboard.state();
-
-                if key.code == KeyCode::Esc {
-                    // use key `Esc` to restore to normal mode
-                    self.toggle_state(current_state)?;
-                    continue;
+                match input {
+                    EditorInput::Key(key) => self.components.resolve(current_state, key)?,
+                    EditorInput::Action(action) => {
+                        self.components.resolve_action(current_state, action)?
+                    }
                 }
-                self.components.resolve(current_state, key)?;
-                self.callbacks_resolve(key)?;
+                if let Some(message) = self.components.take_message(current_state) {
+                    self.dashboard.set_message(message)?;
+                }
+                self.callbacks_resolve(input)?;
+                continue;
+            }
+
+            // ignore the shotcuts not bound
+            if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
                 continue;
             }
 
+            // only Shift + Up / Down and Tab keys keep the selection
+            let keeps_selection = match key.code {
+                KeyCode::Up | KeyCode::Down => key.modifiers.contains(KeyModifiers::SHIFT),
+                KeyCode::Tab | KeyCode::BackTab => true,
+                _ => false,
+            };
+            if !keeps_selection {
+                self.clear_selection()?;
+            }
+            // the closers are not stepped over after moving cursor
+            if !matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+                self.auto_closed = 0;
+            }
+
             // will enter matches in normal mode
             match key.code {
-                // input `Escape` to exit
-                KeyCode::Esc => break,
-
+                KeyCode::Up | KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                    self.select_vertical(Direction::from(key.code))?;
+                }
                 KeyCode::Up | KeyCode::Down => {
                     self.move_cursor_vertical(Direction::from(key.code))?;
                     self.render_all()?;
@@ -831,30 +2143,35 @@ impl Editor {
                 KeyCode::Left | KeyCode::Right => {
                     self.move_cursor_horizontal(Direction::from(key.code))?;
                 }
+                KeyCode::Tab if self.selection_anchor.is_some() => self.indent_lines(true)?,
+                KeyCode::Tab => {
+                    self.dashboard.set_state(EditorState::Modified)?;
+                    self.insert_tab()?;
+                }
+                KeyCode::BackTab => self.indent_lines(false)?,
                 KeyCode::Backspace | KeyCode::Enter | KeyCode::Char(_) => {
                     self.dashboard.set_state(EditorState::Modified)?;
                     match key.code {
                         KeyCode::Backspace => self.delete()?,
-                        KeyCode::Enter => {
-                            self.append_event(EditorOperation::InsertLine, |e| e.insert_line())?;
-                        }
+                        KeyCode::Enter => self.break_line()?,
                         KeyCode::Char(ch) => {
                             if !ch.is_ascii() {
                                 // avoid Non-ASCII characters
                                 continue;
                             }
-                            self.append_event(EditorOperation::InsertChar(ch), |e| {
-                                e.insert_char(ch)
-                            })?;
+                            self.type_char(ch)?;
                         }
                         _ => unreachable!(),
                     }
                 }
                 _ => {}
             }
+            self.refresh_bracket_match()?;
             self.dashboard_cursor_pos_refresh()?;
             Terminal::flush()?;
         }
+        self.save_history();
+        self.remove_swap();
         self.close()?;
         return Ok(());
     }
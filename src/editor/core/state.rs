@@ -12,13 +12,26 @@ pub enum EditorState {
     Finding,
     Replacing,
 
-    ReadingHelpMsg
+    ReadingHelpMsg,
+    SearchingFiles,
+    ViewingHistory,
+    Recovering,
 }
 
 impl EditorState {
     pub fn is_component_state(&self) -> bool {
         !matches!(self, Self::Saved | Self::Modified)
     }
+
+    pub fn is_screen_component_state(&self) -> bool {
+        matches!(
+            self,
+            Self::ReadingHelpMsg
+                | Self::SearchingFiles
+                | Self::ViewingHistory
+                | Self::Recovering
+        )
+    }
 }
 
 impl fmt::Display for EditorState {
@@ -34,6 +47,9 @@ impl fmt::Display for EditorState {
             Self::Replacing => "Replacing",
 
             Self::ReadingHelpMsg => "Reading",
+            Self::SearchingFiles => "Searching",
+            Self::ViewingHistory => "History",
+            Self::Recovering => "Recovering",
         };
         write!(f, "{}", str)
     }
@@ -0,0 +1,117 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::{content_hash, state_dir, write_safely};
+
+use super::history::EditorHistory;
+
+// history is cached in `<state dir>/undo/<hash of path>`,
+// the file starts with the path and the hash of file content
+// which the history ends in, followed by the encoded history.
+const HEADER: &str = "rusditor-undo 1";
+
+fn cache_path(dir: &Path, path: &Path) -> Option<(PathBuf, String)> {
+    let path = path.canonicalize().ok()?;
+    let path_str = path.to_string_lossy().to_string();
+    let name = format!("{:016x}", content_hash(path_str.as_bytes()));
+    return Some((dir.join(name), path_str));
+}
+
+// ignores the difference of line breaks.
+#[inline]
+fn normalize(content: &str) -> String {
+    content.lines().collect::<Vec<&str>>().join("\n")
+}
+
+// saves the history if `buffer` is the same as the file content,
+// otherwise current state of history can not be restored
+// when the file is opened next time.
+pub fn save(history: &EditorHistory, path: &Path, buffer: &str) -> io::Result<()> {
+    match state_dir() {
+        Some(dir) => save_in(&dir.join("undo"), history, path, buffer),
+        None => Ok(()),
+    }
+}
+
+fn save_in(dir: &Path, history: &EditorHistory, path: &Path, buffer: &str) -> io::Result<()> {
+    let Some((cache_path, path_str)) = cache_path(dir, path) else {
+        return Ok(());
+    };
+    let content = normalize(&fs::read_to_string(path)?);
+    if content != normalize(buffer) {
+        return Ok(());
+    }
+
+    let text = format!(
+        "{}\n{}\n{:016x}\n{}",
+        HEADER,
+        path_str,
+        content_hash(content.as_bytes()),
+        history.encode()
+    );
+    fs::create_dir_all(dir)?;
+    return write_safely(&cache_path, text.as_bytes());
+}
+
+// returns the cached history if the file is not changed
+// since the history is saved.
+pub fn load(path: &Path, content: &str) -> Option<EditorHistory> {
+    return load_from(&state_dir()?.join("undo"), path, content);
+}
+
+fn load_from(dir: &Path, path: &Path, content: &str) -> Option<EditorHistory> {
+    let (cache_path, path_str) = cache_path(dir, path)?;
+    let text = fs::read_to_string(cache_path).ok()?;
+
+    let mut parts = text.splitn(4, '\n');
+    let hash = format!("{:016x}", content_hash(normalize(content).as_bytes()));
+    let is_matched = parts.next()? == HEADER
+        && parts.next()? == path_str
+        && parts.next()? == hash;
+    if !is_matched {
+        return None;
+    }
+    return EditorHistory::decode(parts.next()?);
+}
+
+#[test]
+fn undo_cache_test() {
+    use super::event::{EditorEvent, EditorOperation};
+    use crate::editor::cursor_pos::EditorCursorPos;
+
+    let dir = std::env::temp_dir().join(format!("rusditor-undo-test-{}", std::process::id()));
+    let cache_dir = dir.join("undo");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("a.txt");
+    fs::write(&path, "ab\r\ncd\r\n").unwrap();
+
+    let mut history = EditorHistory::new();
+    history.append(EditorEvent {
+        op: EditorOperation::InsertChar('b'),
+        pos_before: EditorCursorPos { row: 1, col: 2 },
+        pos_after: EditorCursorPos { row: 1, col: 3 },
+    });
+    let summaries = |history: &EditorHistory| {
+        history
+            .rows()
+            .into_iter()
+            .map(|row| row.summary)
+            .collect::<Vec<String>>()
+    };
+
+    // the buffer differs from the file, nothing is cached
+    save_in(&cache_dir, &history, &path, "ab\ncd\nef").unwrap();
+    assert!(load_from(&cache_dir, &path, "ab\ncd\n").is_none());
+
+    save_in(&cache_dir, &history, &path, "ab\ncd\n").unwrap();
+    let loaded = load_from(&cache_dir, &path, "ab\ncd\n").unwrap();
+    assert_eq!(summaries(&loaded), summaries(&history));
+
+    // the file is changed on disk after the history is saved
+    fs::write(&path, "ab\ncd\nef\n").unwrap();
+    assert!(load_from(&cache_dir, &path, "ab\ncd\nef\n").is_none());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -18,10 +18,7 @@ impl EditorCursorPos {
     #[allow(unused_assignments)]
     pub fn parse(value: &str) -> Option<Self> {
         fn str_to_num(s: &str) -> Option<usize> {
-            match s.parse::<usize>() {
-                Ok(num) => Some(num),
-                Err(_) => None,
-            }
+            s.parse::<usize>().ok()
         }
 
         let chars = value.chars();
@@ -45,6 +42,17 @@ impl EditorCursorPos {
         col = str_to_num(&number_str)?;
         return Some(EditorCursorPos { row, col });
     }
+
+    // whether the position is on a char boundary of `lines`,
+    // the position right after the line end is included.
+    pub fn is_within(&self, lines: &[&str]) -> bool {
+        let EditorCursorPos { row, col } = *self;
+        if row == 0 || row > lines.len() || col == 0 {
+            return false;
+        }
+        let line = lines[row - 1];
+        return col <= line.len() + 1 && line.is_char_boundary(col - 1);
+    }
 }
 
 // default display
@@ -76,3 +84,21 @@ impl TerminalCursorPos {
         return Ok(());
     }
 }
+
+#[test]
+fn cursor_pos_within_test() {
+    let lines = ["éa", "b"];
+    let pos = |row, col| EditorCursorPos { row, col };
+    assert!(pos(1, 1).is_within(&lines));
+    assert!(pos(1, 3).is_within(&lines));
+    assert!(pos(1, 4).is_within(&lines));
+    assert!(pos(2, 2).is_within(&lines));
+    // the column inside a multibyte char
+    assert!(!pos(1, 2).is_within(&lines));
+    assert!(!pos(1, 5).is_within(&lines));
+    assert!(!pos(3, 1).is_within(&lines));
+    assert!(!pos(2, 0).is_within(&lines));
+    // the row 0 is accepted by parsing but is out of range
+    let target = EditorCursorPos::parse("0:5").unwrap();
+    assert!(!target.is_within(&lines));
+}
@@ -1,6 +1,6 @@
 use crossterm::event::KeyCode;
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Direction {
     Up,
     Down,
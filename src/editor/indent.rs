@@ -0,0 +1,196 @@
+use std::fmt;
+
+use super::{language::Language, pairs::BRACKET_PAIRS};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IndentStyle {
+    Tabs,
+    Spaces(usize),
+}
+
+impl IndentStyle {
+    // the text of one indent level
+    pub fn unit(&self) -> String {
+        match self {
+            Self::Tabs => String::from("\t"),
+            Self::Spaces(width) => " ".repeat(*width),
+        }
+    }
+}
+
+impl fmt::Display for IndentStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tabs => write!(f, "Tabs"),
+            Self::Spaces(width) => write!(f, "Spaces: {}", width),
+        }
+    }
+}
+
+#[inline]
+pub fn is_closing_bracket(ch: char) -> bool {
+    BRACKET_PAIRS.iter().any(|(_, close)| *close == ch)
+}
+
+pub fn leading_whitespace(line: &str) -> &str {
+    let end = line.len() - line.trim_start().len();
+    return &line[..end];
+}
+
+// whether the line after `text` should be indented
+// one more level, e.g. after `{` or `:` in Python.
+pub fn opens_block(text: &str, language: Language) -> bool {
+    let Some(last) = text.trim_end().chars().last() else {
+        return false;
+    };
+    return BRACKET_PAIRS.iter().any(|(open, _)| *open == last)
+        || language.indent_triggers().contains(&last);
+}
+
+// whether the cursor is between a pair of brackets like `{|}`,
+// `before` and `after` are the texts around the cursor.
+pub fn is_between_pair(before: &str, after: &str) -> bool {
+    let last = before.trim_end().chars().last();
+    let next = after.trim_start().chars().next();
+    return BRACKET_PAIRS
+        .iter()
+        .any(|(open, close)| last == Some(*open) && next == Some(*close));
+}
+
+// the count of chars to remove from the end of whitespace
+// `prefix` to outdent one level. trailing spaces are
+// removed to the previous tab stop, and a tab is one level.
+pub fn outdent_len(prefix: &str, tab_width: usize) -> usize {
+    if prefix.ends_with('\t') {
+        return 1;
+    }
+    let space_count = prefix.len() - prefix.trim_end_matches(' ').len();
+    let mut col = 0;
+    for ch in prefix.chars() {
+        col += match ch {
+            '\t' => tab_width - col % tab_width,
+            _ => 1,
+        };
+    }
+    let to_tab_stop = match col % tab_width {
+        0 => tab_width,
+        n => n,
+    };
+    return to_tab_stop.min(space_count);
+}
+
+// the text to remove from the start of `line` to outdent
+// it one level, a tab or spaces up to the tab width.
+pub fn outdent_prefix(line: &str, tab_width: usize) -> &str {
+    if line.starts_with('\t') {
+        return &line[..1];
+    }
+    let space_count = line.len() - line.trim_start_matches(' ').len();
+    return &line[..space_count.min(tab_width)];
+}
+
+// guesses the indent style from leading whitespace of lines,
+// the width of spaces is the most common change of indent
+// between lines. returns None if no line is indented.
+pub fn detect(content: &str) -> Option<IndentStyle> {
+    let (mut tab_lines, mut space_lines) = (0, 0);
+    // counts of indent changes from 2 to 8 spaces
+    let mut changes = [0; 9];
+    let mut last_width = 0;
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        let width = line.len() - line.trim_start_matches(' ').len();
+        if width > 0 {
+            space_lines += 1;
+        }
+        // single spaces are usually alignment, e.g. ` * ` in comments
+        let change = width.abs_diff(last_width);
+        if change >= 2 && change < changes.len() {
+            changes[change] += 1;
+        }
+        last_width = width;
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    if tab_lines >= space_lines {
+        return Some(IndentStyle::Tabs);
+    }
+    let mut width = 0;
+    for (change, count) in changes.iter().enumerate() {
+        if *count > 0 && (width == 0 || *count > changes[width]) {
+            width = change;
+        }
+    }
+    return match width {
+        0 => None,
+        width => Some(IndentStyle::Spaces(width)),
+    };
+}
+
+// converts whitespace `prefix` indented by levels of `from_width`
+// columns to `style`, the columns out of levels are kept as spaces.
+pub fn retab(prefix: &str, from_width: usize, style: IndentStyle) -> String {
+    let mut col = 0;
+    for ch in prefix.chars() {
+        col += match ch {
+            '\t' => from_width - col % from_width,
+            _ => 1,
+        };
+    }
+    return style.unit().repeat(col / from_width) + &" ".repeat(col % from_width);
+}
+
+#[test]
+fn indent_rules_test() {
+    assert_eq!(IndentStyle::Spaces(2).unit(), "  ");
+    assert_eq!(IndentStyle::Tabs.unit(), "\t");
+    assert_eq!(leading_whitespace(" \t fn main() {"), " \t ");
+    assert_eq!(leading_whitespace("    "), "    ");
+
+    assert!(opens_block("fn main() { ", Language::Rust));
+    assert!(opens_block("    let v = vec![", Language::Rust));
+    assert!(!opens_block("    let a = 1;", Language::Rust));
+    assert!(opens_block("if x:", Language::Python));
+    assert!(!opens_block("if x:", Language::Rust));
+    assert!(!opens_block("", Language::Plain));
+
+    assert!(is_between_pair("fn main() {", "}"));
+    assert!(is_between_pair("call(", " )"));
+    assert!(!is_between_pair("call(", "]"));
+
+    assert_eq!(outdent_len("        ", 4), 4);
+    assert_eq!(outdent_len("      ", 4), 2);
+    assert_eq!(outdent_len(" ", 4), 1);
+    assert_eq!(outdent_len("\t\t", 4), 1);
+    assert_eq!(outdent_len("\t  ", 4), 2);
+
+    assert_eq!(outdent_prefix("      let a;", 4), "    ");
+    assert_eq!(outdent_prefix("  let a;", 4), "  ");
+    assert_eq!(outdent_prefix("\t\tlet a;", 4), "\t");
+    assert_eq!(outdent_prefix("let a;", 4), "");
+}
+
+#[test]
+fn indent_detect_test() {
+    let spaces = "fn main() {\n  if a {\n    b();\n  }\n\n  /*\n   * c\n   */\n}\n";
+    assert_eq!(detect(spaces), Some(IndentStyle::Spaces(2)));
+    let tabs = "fn main() {\n\tif a {\n\t\tb();\n\t}\n    // aligned\n}\n";
+    assert_eq!(detect(tabs), Some(IndentStyle::Tabs));
+    assert_eq!(detect("a\nb\n\n"), None);
+    assert_eq!(detect("a\n    b\n    c\n        d\n"), Some(IndentStyle::Spaces(4)));
+
+    assert_eq!(retab("        ", 4, IndentStyle::Tabs), "\t\t");
+    assert_eq!(retab("      ", 4, IndentStyle::Tabs), "\t  ");
+    assert_eq!(retab("\t\t", 4, IndentStyle::Spaces(4)), "        ");
+    assert_eq!(retab("\t  ", 2, IndentStyle::Spaces(4)), "        ");
+    assert_eq!(retab("", 4, IndentStyle::Tabs), "");
+    assert_eq!(IndentStyle::Spaces(2).to_string(), "Spaces: 2");
+}
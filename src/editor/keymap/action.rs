@@ -0,0 +1,218 @@
+use crate::editor::core::EditorState;
+
+// commands which can be bound to keys, the name of an action
+// is used in the `[keys]` section of config file.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EditorAction {
+    // restores to normal mode, or exits in normal mode
+    Cancel,
+    Save,
+    Open,
+    Help,
+    GoTo,
+    Find,
+    Replace,
+    SearchFiles,
+    UndoTree,
+    Undo,
+    Redo,
+    // travel across undo branches
+    HistoryOlder,
+    HistoryNewer,
+    WordLeft,
+    WordRight,
+    // converts indent between tabs and spaces
+    Retab,
+    // jumps to the bracket matching the one at cursor
+    MatchBracket,
+    // comments or uncomments the selected lines
+    ToggleComment,
+    // whole-line editing on the selected lines or the current line
+    DuplicateLines,
+    MoveLinesUp,
+    MoveLinesDown,
+    DeleteLines,
+    JoinLines,
+    InsertLineAbove,
+    InsertLineBelow,
+
+    // actions of finder
+    FindNext,
+    FindPrevious,
+
+    // actions of replacer
+    ReplaceRegex,
+    ReplacePreserveCase,
+    ReplaceNext,
+    ReplaceOne,
+    ReplaceAll,
+    // undoes the last replacing or quits when confirming
+    ReplaceUndo,
+    ReplaceQuit,
+
+    // actions of swap file recoverer
+    RecoverRestore,
+    RecoverDelete,
+    RecoverDiff,
+
+    // actions of find-in-files component
+    SearchFilesRegex,
+    SearchFilesPreserveCase,
+    SearchFilesReplace,
+    SearchFilesExclude,
+    SearchFilesWrite,
+}
+
+impl EditorAction {
+    // the actions and their names
+    pub const ALL: [(Self, &'static str); 42] = [
+        (Self::Cancel, "cancel"),
+        (Self::Save, "save"),
+        (Self::Open, "open"),
+        (Self::Help, "help"),
+        (Self::GoTo, "goto"),
+        (Self::Find, "find"),
+        (Self::Replace, "replace"),
+        (Self::SearchFiles, "search_files"),
+        (Self::UndoTree, "undo_tree"),
+        (Self::Undo, "undo"),
+        (Self::Redo, "redo"),
+        (Self::HistoryOlder, "history.older"),
+        (Self::HistoryNewer, "history.newer"),
+        (Self::WordLeft, "cursor.word_left"),
+        (Self::WordRight, "cursor.word_right"),
+        (Self::Retab, "retab"),
+        (Self::MatchBracket, "cursor.match_bracket"),
+        (Self::ToggleComment, "toggle_comment"),
+        (Self::DuplicateLines, "line.duplicate"),
+        (Self::MoveLinesUp, "line.move_up"),
+        (Self::MoveLinesDown, "line.move_down"),
+        (Self::DeleteLines, "line.delete"),
+        (Self::JoinLines, "line.join"),
+        (Self::InsertLineAbove, "line.insert_above"),
+        (Self::InsertLineBelow, "line.insert_below"),
+        (Self::FindNext, "find.next"),
+        (Self::FindPrevious, "find.previous"),
+        (Self::ReplaceRegex, "replace.regex"),
+        (Self::ReplacePreserveCase, "replace.preserve_case"),
+        (Self::ReplaceNext, "replace.next"),
+        (Self::ReplaceOne, "replace.one"),
+        (Self::ReplaceAll, "replace.all"),
+        (Self::ReplaceUndo, "replace.undo"),
+        (Self::ReplaceQuit, "replace.quit"),
+        (Self::RecoverRestore, "recover.restore"),
+        (Self::RecoverDelete, "recover.delete"),
+        (Self::RecoverDiff, "recover.diff"),
+        (Self::SearchFilesRegex, "search_files.regex"),
+        (Self::SearchFilesPreserveCase, "search_files.preserve_case"),
+        (Self::SearchFilesReplace, "search_files.replace"),
+        (Self::SearchFilesExclude, "search_files.exclude"),
+        (Self::SearchFilesWrite, "search_files.write"),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, action_name)| *action_name == name)
+            .map(|(action, _)| *action)
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(action, _)| action == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+
+    // the key sequences bound by default
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cancel => &["esc"],
+            Self::Save => &["ctrl+s"],
+            Self::Open => &["ctrl+o"],
+            Self::Help => &["ctrl+h"],
+            Self::GoTo => &["ctrl+g"],
+            Self::Find => &["ctrl+f"],
+            Self::Replace => &["ctrl+r"],
+            Self::SearchFiles => &["ctrl+p"],
+            Self::UndoTree => &["ctrl+u"],
+            Self::Undo => &["ctrl+z"],
+            Self::Redo => &["ctrl+y"],
+            Self::HistoryOlder => &["alt+z"],
+            Self::HistoryNewer => &["alt+y"],
+            Self::WordLeft => &["ctrl+left"],
+            Self::WordRight => &["ctrl+right"],
+            Self::Retab => &["ctrl+t"],
+            Self::MatchBracket => &["ctrl+b"],
+            // terminals send Ctrl + / as Ctrl + 7
+            Self::ToggleComment => &["ctrl+/", "ctrl+7"],
+            Self::DuplicateLines => &["ctrl+d"],
+            Self::MoveLinesUp => &["alt+up"],
+            Self::MoveLinesDown => &["alt+down"],
+            // `ctrl+k` is left for key sequences like `ctrl+k ctrl+c`,
+            // terminals without extended keys can not send Ctrl + Shift + k.
+            Self::DeleteLines => &["ctrl+shift+k", "alt+k"],
+            Self::JoinLines => &["ctrl+j"],
+            Self::InsertLineAbove => &["alt+shift+n"],
+            Self::InsertLineBelow => &["alt+n"],
+            Self::FindNext => &["enter"],
+            Self::FindPrevious => &["shift+enter"],
+            Self::ReplaceRegex => &["ctrl+e"],
+            Self::ReplacePreserveCase => &["ctrl+k"],
+            // plain char keys only work when confirming replacements
+            Self::ReplaceNext => &["ctrl+n", "n"],
+            Self::ReplaceOne => &["ctrl+s", "y"],
+            Self::ReplaceAll => &["ctrl+a", "a"],
+            Self::ReplaceUndo => &["u"],
+            Self::ReplaceQuit => &["q"],
+            Self::RecoverRestore => &["r"],
+            Self::RecoverDelete => &["x"],
+            Self::RecoverDiff => &["d"],
+            Self::SearchFilesRegex => &["ctrl+e"],
+            Self::SearchFilesPreserveCase => &["ctrl+k"],
+            Self::SearchFilesReplace => &["ctrl+r"],
+            Self::SearchFilesExclude => &["ctrl+x"],
+            Self::SearchFilesWrite => &["ctrl+s"],
+        }
+    }
+
+    // whether the action works on the selected lines
+    #[inline]
+    pub fn keeps_selection(&self) -> bool {
+        matches!(
+            self,
+            Self::ToggleComment
+                | Self::DuplicateLines
+                | Self::MoveLinesUp
+                | Self::MoveLinesDown
+                | Self::DeleteLines
+                | Self::JoinLines
+        )
+    }
+
+    // the component state the action works in,
+    // None if the action works in any state.
+    pub fn state(&self) -> Option<EditorState> {
+        match self {
+            Self::FindNext | Self::FindPrevious => Some(EditorState::Finding),
+            Self::ReplaceRegex
+            | Self::ReplacePreserveCase
+            | Self::ReplaceNext
+            | Self::ReplaceOne
+            | Self::ReplaceAll
+            | Self::ReplaceUndo
+            | Self::ReplaceQuit => Some(EditorState::Replacing),
+            Self::RecoverRestore | Self::RecoverDelete | Self::RecoverDiff => {
+                Some(EditorState::Recovering)
+            }
+            Self::SearchFilesRegex
+            | Self::SearchFilesPreserveCase
+            | Self::SearchFilesReplace
+            | Self::SearchFilesExclude
+            | Self::SearchFilesWrite => Some(EditorState::SearchingFiles),
+            _ => None,
+        }
+    }
+}
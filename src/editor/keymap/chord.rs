@@ -0,0 +1,164 @@
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// a key with modifiers, e.g. `Ctrl+K`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    // the case of a char already tells whether Shift is pressed,
+    // so Shift is removed from char keys to compare them.
+    fn normalize(mut self) -> Self {
+        if let KeyCode::Char(ch) = self.code {
+            if self.modifiers.contains(KeyModifiers::SHIFT) {
+                self.code = KeyCode::Char(ch.to_ascii_uppercase());
+                self.modifiers.remove(KeyModifiers::SHIFT);
+            }
+        }
+        return self;
+    }
+
+    // whether the chord types a char, such chords are
+    // not resolved as actions when typing text.
+    #[inline]
+    pub fn is_typing(&self) -> bool {
+        self.modifiers.is_empty() && matches!(self.code, KeyCode::Char(_))
+    }
+
+    // parses a chord like `ctrl+k`, `alt+shift+up` or `enter`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = value.split('+').collect();
+        // `ctrl++` means Ctrl and `+`
+        if value.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let (key_name, modifier_names) = parts.split_last()?;
+        for name in modifier_names {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key_name.chars();
+        let code = match (chars.next(), chars.next()) {
+            // `S` alone means Shift + s, while `ctrl+S` is the same as `ctrl+s`
+            (Some(ch), None) if modifiers.is_empty() => KeyCode::Char(ch),
+            (Some(ch), None) => KeyCode::Char(ch.to_ascii_lowercase()),
+            _ => match key_name.to_ascii_lowercase().as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => {
+                    let number = name.strip_prefix('f')?.parse::<u8>().ok()?;
+                    if !(1..=12).contains(&number) {
+                        return None;
+                    }
+                    KeyCode::F(number)
+                }
+            },
+        };
+        return Some(Self { code, modifiers }.normalize());
+    }
+
+    // parses chords separated by spaces, e.g. `ctrl+k ctrl+c`.
+    pub fn parse_sequence(value: &str) -> Option<Vec<Self>> {
+        let sequence: Option<Vec<Self>> = value.split_whitespace().map(Self::parse).collect();
+        return sequence.filter(|s| !s.is_empty());
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers).normalize()
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut modifiers = self.modifiers;
+        if let KeyCode::Char(ch) = self.code {
+            if !modifiers.is_empty() && ch.is_ascii_uppercase() {
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+        }
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl"),
+            (KeyModifiers::ALT, "Alt"),
+            (KeyModifiers::SHIFT, "Shift"),
+        ] {
+            if modifiers.contains(modifier) {
+                write!(f, "{} + ", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(ch) if self.modifiers.is_empty() => write!(f, "{}", ch),
+            KeyCode::Char(ch) => write!(f, "{}", ch.to_ascii_uppercase()),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[test]
+fn key_chord_parse_test() {
+    let chord = |code, modifiers| KeyChord::new(code, modifiers);
+    assert_eq!(
+        KeyChord::parse("ctrl+s"),
+        Some(chord(KeyCode::Char('s'), KeyModifiers::CONTROL))
+    );
+    assert_eq!(
+        KeyChord::parse("Shift+Enter"),
+        Some(chord(KeyCode::Enter, KeyModifiers::SHIFT))
+    );
+    assert_eq!(
+        KeyChord::parse("alt+shift+a"),
+        Some(chord(KeyCode::Char('A'), KeyModifiers::ALT))
+    );
+    assert_eq!(
+        KeyChord::parse("ctrl++"),
+        Some(chord(KeyCode::Char('+'), KeyModifiers::CONTROL))
+    );
+    assert_eq!(
+        KeyChord::parse("Y"),
+        Some(chord(KeyCode::Char('Y'), KeyModifiers::NONE))
+    );
+    assert_eq!(KeyChord::parse("hyper+s"), None);
+    assert_eq!(KeyChord::parse("f13"), None);
+
+    // the chord of a key event with Shift
+    let key = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+    assert_eq!(KeyChord::from(key), chord(KeyCode::Char('A'), KeyModifiers::NONE));
+
+    let sequence = KeyChord::parse_sequence("ctrl+k  ctrl+c").unwrap();
+    let names: Vec<String> = sequence.iter().map(|c| c.to_string()).collect();
+    assert_eq!(names, vec!["Ctrl + K", "Ctrl + C"]);
+    assert_eq!(KeyChord::parse_sequence(""), None);
+}
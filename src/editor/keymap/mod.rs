@@ -0,0 +1,174 @@
+mod action;
+mod chord;
+
+pub use action::EditorAction;
+pub use chord::KeyChord;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::core::EditorState;
+
+// a key press, or the action its key sequence is bound to.
+#[derive(Clone, Copy)]
+pub enum EditorInput {
+    Key(KeyEvent),
+    Action(EditorAction),
+}
+
+impl EditorInput {
+    #[inline]
+    pub fn is_action(&self, target: EditorAction) -> bool {
+        matches!(self, Self::Action(action) if *action == target)
+    }
+
+    // whether the input is `code` without modifiers
+    #[inline]
+    pub fn is_key(&self, code: KeyCode) -> bool {
+        matches!(self, Self::Key(key) if key.modifiers == KeyModifiers::NONE && key.code == code)
+    }
+}
+
+pub enum KeyMatch {
+    Action(EditorAction),
+    // the keys are the prefix of some key sequences
+    Pending,
+    None,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyChord>, EditorAction)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = vec![];
+        for (action, _) in EditorAction::ALL {
+            for keys in action.default_keys() {
+                if let Some(sequence) = KeyChord::parse_sequence(keys) {
+                    bindings.push((sequence, action));
+                }
+            }
+        }
+        return Self { bindings };
+    }
+}
+
+impl Keymap {
+    // replaces the key sequences bound to `action`.
+    pub fn bind(&mut self, action: EditorAction, sequences: Vec<Vec<KeyChord>>) {
+        self.bindings.retain(|(_, bound)| *bound != action);
+        for sequence in sequences {
+            self.bindings.push((sequence, action));
+        }
+    }
+
+    // finds a sequence bound to another action which conflicts with
+    // `sequence`, when one is the prefix of the other, the longer one
+    // can never be resolved. actions working in different states do
+    // not conflict, as the actions of component take precedence.
+    pub fn find_conflict(
+        &self,
+        action: EditorAction,
+        sequence: &[KeyChord],
+    ) -> Option<(&[KeyChord], EditorAction)> {
+        return self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| *bound != action && bound.state() == action.state())
+            .find(|(bound_sequence, _)| {
+                bound_sequence.starts_with(sequence) || sequence.starts_with(bound_sequence)
+            })
+            .map(|(bound_sequence, bound)| (bound_sequence.as_slice(), *bound));
+    }
+
+    // finds the action for keys pressed in `state`, actions of the
+    // component take precedence over the ones working in any state.
+    // when `is_typing`, sequences starting with a char key are skipped.
+    pub fn resolve(&self, keys: &[KeyChord], state: EditorState, is_typing: bool) -> KeyMatch {
+        let candidates: Vec<&(Vec<KeyChord>, EditorAction)> = self
+            .bindings
+            .iter()
+            .filter(|(sequence, action)| {
+                action.state().is_none_or(|s| s == state)
+                    && sequence.starts_with(keys)
+                    && !(is_typing && sequence[0].is_typing())
+            })
+            .collect();
+
+        let exact_matched = candidates
+            .iter()
+            .filter(|(sequence, _)| sequence.len() == keys.len());
+        let matched = exact_matched
+            .clone()
+            .find(|(_, action)| action.state().is_some())
+            .or(exact_matched.clone().next());
+        if let Some((_, action)) = matched {
+            return KeyMatch::Action(*action);
+        }
+        if !candidates.is_empty() {
+            return KeyMatch::Pending;
+        }
+        return KeyMatch::None;
+    }
+}
+
+#[test]
+fn keymap_resolve_test() {
+    let chords = |value| KeyChord::parse_sequence(value).unwrap();
+    let resolve_with = |keymap: &Keymap, keys, state, is_typing| {
+        let matched = keymap.resolve(&chords(keys), state, is_typing);
+        return match matched {
+            KeyMatch::Action(action) => format!("{:?}", action),
+            KeyMatch::Pending => String::from("pending"),
+            KeyMatch::None => String::from("none"),
+        };
+    };
+    let resolve = |keymap: &Keymap, keys, state| resolve_with(keymap, keys, state, true);
+
+    let mut keymap = Keymap::default();
+    assert_eq!(resolve(&keymap, "ctrl+s", EditorState::Saved), "Save");
+    assert_eq!(resolve(&keymap, "ctrl+s", EditorState::Replacing), "ReplaceOne");
+    assert_eq!(resolve(&keymap, "enter", EditorState::Saved), "none");
+    assert_eq!(resolve(&keymap, "enter", EditorState::Finding), "FindNext");
+    // left for key sequences
+    assert_eq!(resolve(&keymap, "ctrl+k", EditorState::Saved), "none");
+    // char keys are typed into the text, unless no text is typed
+    assert_eq!(resolve(&keymap, "y", EditorState::Replacing), "none");
+    assert_eq!(
+        resolve_with(&keymap, "y", EditorState::Replacing, false),
+        "ReplaceOne"
+    );
+    assert_eq!(
+        resolve_with(&keymap, "x", EditorState::Recovering, false),
+        "RecoverDelete"
+    );
+
+    keymap.bind(EditorAction::Save, vec![chords("ctrl+x ctrl+s")]);
+    assert_eq!(resolve(&keymap, "ctrl+s", EditorState::Saved), "none");
+    assert_eq!(resolve(&keymap, "ctrl+x", EditorState::Saved), "pending");
+    assert_eq!(resolve(&keymap, "ctrl+x ctrl+s", EditorState::Saved), "Save");
+    // the component action is used before the sequence is finished
+    assert_eq!(
+        resolve(&keymap, "ctrl+x", EditorState::SearchingFiles),
+        "SearchFilesExclude"
+    );
+
+    // a sequence starting with a bound chord never fires
+    let conflict = |keymap: &Keymap, action, keys| {
+        keymap
+            .find_conflict(action, &chords(keys))
+            .map(|(_, action)| format!("{:?}", action))
+    };
+    keymap.bind(EditorAction::Undo, vec![chords("ctrl+k")]);
+    assert_eq!(
+        conflict(&keymap, EditorAction::Redo, "ctrl+k ctrl+c"),
+        Some(String::from("Undo"))
+    );
+    assert_eq!(conflict(&keymap, EditorAction::Undo, "ctrl+k ctrl+c"), None);
+    assert_eq!(
+        conflict(&keymap, EditorAction::Redo, "ctrl+x"),
+        Some(String::from("Save"))
+    );
+    // the component action takes precedence in its state
+    assert_eq!(conflict(&keymap, EditorAction::Redo, "ctrl+x ctrl+y"), None);
+}
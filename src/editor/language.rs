@@ -0,0 +1,86 @@
+use std::path::Path;
+
+// the language of edited file, guessed by the file name.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Language {
+    Rust,
+    C,
+    Java,
+    JavaScript,
+    Go,
+    Python,
+    Shell,
+    Toml,
+    Yaml,
+    Sql,
+    Lua,
+    Html,
+    Markdown,
+    Plain,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "rs" => Self::Rust,
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" => Self::C,
+            "java" | "kt" | "scala" => Self::Java,
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" => Self::JavaScript,
+            "go" => Self::Go,
+            "py" | "pyw" => Self::Python,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            "toml" => Self::Toml,
+            "yml" | "yaml" => Self::Yaml,
+            "sql" => Self::Sql,
+            "lua" => Self::Lua,
+            "html" | "htm" | "xml" | "svg" | "vue" => Self::Html,
+            "md" | "markdown" => Self::Markdown,
+            _ => Self::Plain,
+        }
+    }
+
+    // chars at line end which open an indented block
+    // besides opening brackets, e.g. `:` in Python.
+    pub fn indent_triggers(&self) -> &'static [char] {
+        match self {
+            Self::Python | Self::Yaml => &[':'],
+            _ => &[],
+        }
+    }
+
+    // the token starting a comment to the end of line
+    pub fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Self::Rust | Self::C | Self::Java | Self::JavaScript | Self::Go => Some("//"),
+            Self::Python | Self::Shell | Self::Toml | Self::Yaml => Some("#"),
+            Self::Sql | Self::Lua => Some("--"),
+            Self::Html | Self::Markdown | Self::Plain => None,
+        }
+    }
+
+    pub fn block_comment(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Rust | Self::C | Self::Java | Self::JavaScript | Self::Go | Self::Sql => {
+                Some(("/*", "*/"))
+            }
+            Self::Html | Self::Markdown => Some(("<!--", "-->")),
+            _ => None,
+        }
+    }
+
+    // the chars quoting strings in a line, `'` in Rust
+    // is left out since it also starts lifetimes.
+    pub fn quotes(&self) -> &'static [char] {
+        match self {
+            Self::Rust => &['"'],
+            Self::JavaScript | Self::Go => &['"', '\'', '`'],
+            Self::Markdown | Self::Plain => &[],
+            _ => &['"', '\''],
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+// how long a message stays in the dashboard
+pub const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+// a transient message shown in the dashboard,
+// used to report user mistakes and failures.
+#[derive(Debug, Clone)]
+pub struct EditorMessage {
+    pub level: MessageLevel,
+    pub text: String,
+}
+
+impl EditorMessage {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            level: MessageLevel::Info,
+            text: text.into(),
+        }
+    }
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self {
+            level: MessageLevel::Warning,
+            text: text.into(),
+        }
+    }
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            level: MessageLevel::Error,
+            text: text.into(),
+        }
+    }
+}
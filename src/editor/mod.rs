@@ -1,8 +1,21 @@
 mod components;
+mod comment;
+mod config;
 
 mod cursor_pos;
 mod direction;
+mod indent;
+mod keymap;
+mod language;
+mod message;
+mod pairs;
+mod pattern;
+mod project_search;
+mod swap_file;
 mod text_area;
+mod theme;
 
 mod core;
 pub use self::core::Editor;
+pub use config::{ConfigValue, EditorConfig};
+pub use theme::Theme;
@@ -0,0 +1,169 @@
+use super::language::Language;
+
+// the pairs of brackets, quotes are closed by themselves.
+pub const BRACKET_PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 2] = ['"', '\''];
+
+// the closer inserted when `ch` is typed
+pub fn closer_of(ch: char) -> Option<char> {
+    if QUOTES.contains(&ch) {
+        return Some(ch);
+    }
+    return BRACKET_PAIRS
+        .iter()
+        .find(|(open, _)| *open == ch)
+        .map(|(_, close)| *close);
+}
+
+#[inline]
+pub fn is_closer(ch: char) -> bool {
+    QUOTES.contains(&ch) || BRACKET_PAIRS.iter().any(|(_, close)| *close == ch)
+}
+
+#[inline]
+pub fn is_bracket(ch: char) -> bool {
+    BRACKET_PAIRS
+        .iter()
+        .any(|(open, close)| *open == ch || *close == ch)
+}
+
+#[inline]
+pub fn is_quote(ch: char) -> bool {
+    QUOTES.contains(&ch)
+}
+
+#[inline]
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+// whether the chars around cursor are a pair with nothing
+// in between, e.g. `(|)` or `"|"`.
+pub fn is_empty_pair(before: Option<char>, after: Option<char>) -> bool {
+    let (Some(before), Some(after)) = (before, after) else {
+        return false;
+    };
+    return closer_of(before) == Some(after);
+}
+
+// the brackets out of strings and comments of `language`
+// as (row, byte index, bracket), rows start from 0.
+fn code_brackets(lines: &[&str], language: Language) -> Vec<(usize, usize, char)> {
+    let line_comment = language.line_comment();
+    let block_comment = language.block_comment();
+    let quotes = language.quotes();
+
+    let mut brackets = vec![];
+    let mut is_in_block_comment = false;
+    for (row, line) in lines.iter().enumerate() {
+        // strings end with the line
+        let mut quote = None;
+        let mut chars = line.char_indices();
+        while let Some((index, ch)) = chars.next() {
+            let rest = &line[index..];
+            if is_in_block_comment {
+                if let Some((_, end)) = block_comment.filter(|(_, end)| rest.starts_with(end)) {
+                    is_in_block_comment = false;
+                    chars.nth(end.len() - 2);
+                }
+                continue;
+            }
+            if let Some(quote_ch) = quote {
+                match ch {
+                    '\\' => _ = chars.next(),
+                    ch if ch == quote_ch => quote = None,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if line_comment.is_some_and(|token| rest.starts_with(token)) {
+                break;
+            }
+            if let Some((start, _)) = block_comment.filter(|(start, _)| rest.starts_with(start)) {
+                is_in_block_comment = true;
+                chars.nth(start.len() - 2);
+            } else if quotes.contains(&ch) {
+                quote = Some(ch);
+            } else if is_bracket(ch) {
+                brackets.push((row, index, ch));
+            }
+        }
+    }
+    return brackets;
+}
+
+// finds the bracket matching the one at `row` and byte `index`,
+// brackets in strings and comments are skipped.
+pub fn find_match(
+    lines: &[&str],
+    row: usize,
+    index: usize,
+    language: Language,
+) -> Option<(usize, usize)> {
+    let brackets = code_brackets(lines, language);
+    let at = brackets
+        .iter()
+        .position(|(r, i, _)| *r == row && *i == index)?;
+    let ch = brackets[at].2;
+    let (open, close) = *BRACKET_PAIRS
+        .iter()
+        .find(|(open, close)| *open == ch || *close == ch)?;
+
+    // searches forward from an opener, backward from a closer
+    let (candidates, target): (Vec<_>, _) = match ch == open {
+        true => (brackets[at..].iter().collect(), close),
+        false => (brackets[..=at].iter().rev().collect(), open),
+    };
+    let mut depth = 0;
+    for (r, i, c) in candidates {
+        if *c == ch {
+            depth += 1;
+        } else if *c == target {
+            depth -= 1;
+            if depth == 0 {
+                return Some((*r, *i));
+            }
+        }
+    }
+    return None;
+}
+
+#[test]
+fn pairs_test() {
+    assert_eq!(closer_of('('), Some(')'));
+    assert_eq!(closer_of('"'), Some('"'));
+    assert_eq!(closer_of(')'), None);
+    assert!(is_closer('}'));
+    assert!(is_closer('\''));
+    assert!(!is_closer('{'));
+    assert!(is_word_char('_'));
+    assert!(!is_word_char('.'));
+
+    assert!(is_empty_pair(Some('['), Some(']')));
+    assert!(is_empty_pair(Some('\''), Some('\'')));
+    assert!(!is_empty_pair(Some('('), Some(']')));
+    assert!(!is_empty_pair(None, Some(')')));
+}
+
+#[test]
+fn find_match_test() {
+    let lines = [
+        "fn main() {",
+        "    let s = \"}\"; // }",
+        "    /* { */ if a[0] {",
+        "    }",
+        "}",
+    ];
+    let find = |row, index| find_match(&lines, row, index, Language::Rust);
+    assert_eq!(find(0, 10), Some((4, 0)));
+    assert_eq!(find(4, 0), Some((0, 10)));
+    assert_eq!(find(0, 7), Some((0, 8)));
+    assert_eq!(find(2, 20), Some((3, 4)));
+    assert_eq!(find(2, 16), Some((2, 18)));
+    // brackets in strings and comments are not matched
+    assert_eq!(find(1, 13), None);
+    assert_eq!(find(2, 7), None);
+    // plain texts have no strings or comments
+    assert_eq!(find_match(&lines, 1, 13, Language::Plain), Some((0, 10)));
+}
@@ -0,0 +1,275 @@
+use regex::{Regex, RegexBuilder};
+
+use super::cursor_pos::EditorCursorPos;
+
+// the searching pattern used by finder and replacer,
+// matches literal text or regular expression.
+// patterns are matched against the whole buffer with lines
+// joined by `\n`, so that matches can span line breaks.
+pub enum SearchPattern {
+    Literal(String),
+    // literal text matched case-insensitively,
+    // used by case-preserving replacement.
+    LiteralIgnoreCase(Regex),
+    Regex(Regex),
+}
+
+impl SearchPattern {
+    // returns None when the pattern is empty or
+    // is an invalid regular expression.
+    pub fn new(pat: &str, use_regex: bool) -> Option<Self> {
+        if pat.is_empty() {
+            return None;
+        }
+        if use_regex {
+            RegexBuilder::new(pat)
+                .multi_line(true)
+                .build()
+                .ok()
+                .map(Self::Regex)
+        } else {
+            Some(Self::Literal(pat.to_owned()))
+        }
+    }
+
+    // the pattern for replacing, escape sequences in literal text
+    // are converted, e.g. `\n` to match a line break. literal text
+    // matches in any case when the replacement preserves the case.
+    pub fn for_replacing(pat: &str, use_regex: bool, preserve_case: bool) -> Option<Self> {
+        let pat = match use_regex {
+            true => pat.to_owned(),
+            false => unescape(pat),
+        };
+        match Self::new(&pat, use_regex)? {
+            Self::Literal(text) if preserve_case => {
+                let re = RegexBuilder::new(&regex::escape(&text))
+                    .case_insensitive(true)
+                    .build()
+                    .ok()?;
+                Some(Self::LiteralIgnoreCase(re))
+            }
+            pattern => Some(pattern),
+        }
+    }
+
+    // returns the byte ranges `(start, end)` of all matches.
+    pub fn find_all(&self, text: &str) -> Vec<(usize, usize)> {
+        match self {
+            Self::Literal(pat) => text
+                .match_indices(pat.as_str())
+                .map(|(pos, str)| (pos, pos + str.len()))
+                .collect(),
+            Self::LiteralIgnoreCase(re) | Self::Regex(re) => re
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+        }
+    }
+
+    // returns the byte range of the first matching
+    // starts at or after `start`.
+    pub fn find_from(&self, text: &str, start: usize) -> Option<(usize, usize)> {
+        match self {
+            Self::Literal(pat) => {
+                let pos = start + text.get(start..)?.find(pat.as_str())?;
+                Some((pos, pos + pat.len()))
+            }
+            Self::LiteralIgnoreCase(re) | Self::Regex(re) => {
+                let mut start = start;
+                loop {
+                    let m = re.find_at(text, start)?;
+                    if !m.is_empty() {
+                        return Some((m.start(), m.end()));
+                    }
+                    // skip the empty matching
+                    start = m.end() + text[m.end()..].chars().next()?.len_utf8();
+                }
+            }
+        }
+    }
+
+    // returns the cursor positions of all matches' start.
+    pub fn find_all_pos(&self, text: &str) -> Vec<EditorCursorPos> {
+        let mut pos_list = vec![];
+        let (mut row, mut line_start) = (1, 0);
+        let mut line_breaks = text.match_indices('\n').map(|(i, _)| i).peekable();
+
+        for (start, _) in self.find_all(text) {
+            while let Some(&break_index) = line_breaks.peek() {
+                if break_index >= start {
+                    break;
+                }
+                row += 1;
+                line_start = break_index + 1;
+                line_breaks.next();
+            }
+            pos_list.push(EditorCursorPos {
+                row,
+                col: start - line_start + 1,
+            });
+        }
+        return pos_list;
+    }
+
+    // matches the pattern exactly at `start` of `text`, returns the
+    // matched text and the expanded replacement text.
+    pub fn expand_at(
+        &self,
+        text: &str,
+        start: usize,
+        replacement: &str,
+        preserve_case: bool,
+    ) -> Option<(String, String)> {
+        let (matched, mut replaced) = match self {
+            Self::Literal(pat) => {
+                if !text.get(start..)?.starts_with(pat.as_str()) {
+                    return None;
+                }
+                (pat.clone(), unescape(replacement))
+            }
+            Self::LiteralIgnoreCase(re) => {
+                let m = re.find_at(text, start)?;
+                if m.start() != start {
+                    return None;
+                }
+                (m.as_str().to_owned(), unescape(replacement))
+            }
+            Self::Regex(re) => {
+                let caps = re.captures_at(text, start)?;
+                let whole = caps.get(0)?;
+                if whole.start() != start || whole.is_empty() {
+                    return None;
+                }
+                let mut replaced = String::new();
+                caps.expand(&unescape(replacement), &mut replaced);
+                (whole.as_str().to_owned(), replaced)
+            }
+        };
+
+        if preserve_case {
+            replaced = apply_case(&matched, &replaced);
+        }
+        return Some((matched, replaced));
+    }
+}
+
+// converts escape sequences in replacement text.
+// e.g.
+//   in : "a\\tb\\n"
+//   out: "a\tb\n"
+pub fn unescape(str: &str) -> String {
+    let mut result = String::new();
+    let mut chars = str.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    return result;
+}
+
+// makes the replacement text follow the case of matched text.
+// e.g.
+//   "foo" -> "bar"
+//   "Foo" -> "Bar"
+//   "FOO" -> "BAR"
+pub fn apply_case(matched: &str, replacement: &str) -> String {
+    let has_upper = matched.chars().any(|ch| ch.is_uppercase());
+    let has_lower = matched.chars().any(|ch| ch.is_lowercase());
+
+    if has_upper && !has_lower {
+        return replacement.to_uppercase();
+    }
+    let is_capitalized = matched.chars().next().is_some_and(|ch| ch.is_uppercase());
+    if is_capitalized {
+        let mut chars = replacement.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+    }
+    return replacement.to_owned();
+}
+
+#[test]
+fn pattern_expand_test() {
+    let pattern = SearchPattern::new(r"(\w+)@(?<host>\w+)", true).unwrap();
+    let expanded = pattern.expand_at("to: foo@bar", 4, r"${host}\t$1", false);
+    assert_eq!(
+        expanded,
+        Some((String::from("foo@bar"), String::from("bar\tfoo")))
+    );
+    assert_eq!(pattern.expand_at("to: foo@bar", 3, "", false), None);
+
+    let pattern = SearchPattern::new("foo", false).unwrap();
+    assert_eq!(pattern.find_all("foo Foo foo"), vec![(0, 3), (8, 11)]);
+    assert_eq!(pattern.find_from("foo Foo foo", 1), Some((8, 11)));
+    assert_eq!(pattern.find_from("foo Foo foo", 9), None);
+
+    let pattern = SearchPattern::new("x*", true).unwrap();
+    assert_eq!(pattern.find_from("abxx", 0), Some((2, 4)));
+
+    assert_eq!(apply_case("foo", "bar"), "bar");
+    assert_eq!(apply_case("Foo", "bar"), "Bar");
+    assert_eq!(apply_case("FOO", "bar"), "BAR");
+
+    // literal text matches in any case when preserving case
+    let text = "foo Foo FOO";
+    let pattern = SearchPattern::for_replacing("foo", false, true).unwrap();
+    let mut replaced = String::new();
+    let mut last_end = 0;
+    for (start, end) in pattern.find_all(text) {
+        let (_, to) = pattern.expand_at(text, start, "bar", true).unwrap();
+        replaced += &text[last_end..start];
+        replaced += &to;
+        last_end = end;
+    }
+    replaced += &text[last_end..];
+    assert_eq!(replaced, "bar Bar BAR");
+    let pattern = SearchPattern::for_replacing("foo", false, false).unwrap();
+    assert_eq!(pattern.find_all(text), vec![(0, 3)]);
+}
+
+#[test]
+fn pattern_find_all_pos_test() {
+    let pattern = SearchPattern::new("abc", false).unwrap();
+    let pos_list = pattern.find_all_pos("abc  abc  abc");
+    assert_eq!(pos_list.iter().map(|p| p.col).collect::<Vec<usize>>(), vec![1, 6, 11]);
+
+    let pattern = SearchPattern::for_replacing(r"b\nc", false, false).unwrap();
+    let pos_list = pattern.find_all_pos("ab\ncd\nab\nc");
+    assert_eq!(
+        pos_list,
+        vec![
+            EditorCursorPos { row: 1, col: 2 },
+            EditorCursorPos { row: 3, col: 2 },
+        ]
+    );
+
+    // finder matches escape sequences literally
+    let pattern = SearchPattern::new(r"\t", false).unwrap();
+    assert_eq!(pattern.find_all("a\tb\\tc"), vec![(3, 5)]);
+
+    let pattern = SearchPattern::new(r"^\w+$", true).unwrap();
+    let pos_list = pattern.find_all_pos("foo\n  bar\nbaz");
+    assert_eq!(
+        pos_list,
+        vec![
+            EditorCursorPos { row: 1, col: 1 },
+            EditorCursorPos { row: 3, col: 1 },
+        ]
+    );
+}
@@ -0,0 +1,212 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::{walk_files, write_safely};
+
+use super::{cursor_pos::EditorCursorPos, pattern::SearchPattern};
+
+// a matching in files under a directory.
+#[derive(Clone)]
+pub struct FileMatch {
+    pub path: PathBuf,
+    pub pos: EditorCursorPos,
+    // the line where the matching starts
+    pub context: String,
+}
+
+impl fmt::Display for FileMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.pos.row,
+            self.pos.col,
+            self.context.trim()
+        )
+    }
+}
+
+// the maximum count of matchings to collect,
+// avoid too long time of searching.
+pub const MAX_MATCH_COUNT: usize = 1000;
+
+// returns None if the file can not be read or is binary file.
+pub fn read_text_file(path: &Path) -> Option<String> {
+    // `8000` here is the size of head bytes that git
+    // checks NUL byte in to detect binary file.
+    const BINARY_CHECK_SIZE: usize = 8000;
+
+    let bytes = fs::read(path).ok()?;
+    let check_range = ..bytes.len().min(BINARY_CHECK_SIZE);
+    if bytes[check_range].contains(&0) {
+        return None;
+    }
+    return String::from_utf8(bytes).ok();
+}
+
+// returns the matchings and whether they are truncated
+// at `MAX_MATCH_COUNT`.
+pub fn search_files(root: &Path, pattern: &SearchPattern) -> (Vec<FileMatch>, bool) {
+    let mut result = vec![];
+    for path in walk_files(root) {
+        let Some(content) = read_text_file(&path) else {
+            continue;
+        };
+        let lines = content.lines().collect::<Vec<&str>>();
+        let display_path = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+
+        for pos in pattern.find_all_pos(&content) {
+            result.push(FileMatch {
+                path: display_path.clone(),
+                pos,
+                context: lines.get(pos.row - 1).unwrap_or(&"").to_string(),
+            });
+            if result.len() >= MAX_MATCH_COUNT {
+                return (result, true);
+            }
+        }
+    }
+    return (result, false);
+}
+
+// a single replacement in file.
+pub struct Hunk {
+    pub pos: EditorCursorPos,
+    // byte offset of the matching in file
+    start: usize,
+    from: String,
+    to: String,
+
+    // the line before and after replacing
+    pub before: String,
+    pub after: String,
+
+    pub accepted: bool,
+}
+
+// replacements in a single file.
+pub struct FileReplacement {
+    pub path: PathBuf,
+    // the file content when previewing, used to avoid
+    // overwriting changes made after previewing.
+    content: String,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileReplacement {
+    #[inline]
+    pub fn accepted_count(&self) -> usize {
+        self.hunks.iter().filter(|h| h.accepted).count()
+    }
+
+    // returns the content with all accepted hunks applied.
+    fn replaced_content(&self) -> String {
+        let mut result = self.content.clone();
+        // apply from the last hunk, so that offsets
+        // of hunks before are not shifted.
+        for hunk in self.hunks.iter().rev().filter(|h| h.accepted) {
+            result.replace_range(hunk.start..(hunk.start + hunk.from.len()), &hunk.to);
+        }
+        return result;
+    }
+}
+
+pub fn replace_preview(
+    root: &Path,
+    pattern: &SearchPattern,
+    replacement: &str,
+    preserve_case: bool,
+) -> Vec<FileReplacement> {
+    let mut result = vec![];
+    for path in walk_files(root) {
+        let Some(content) = read_text_file(&path) else {
+            continue;
+        };
+        let ranges = pattern.find_all(&content);
+        if ranges.is_empty() {
+            continue;
+        }
+
+        let lines = content.lines().collect::<Vec<&str>>();
+        let pos_list = pattern.find_all_pos(&content);
+        let mut hunks = vec![];
+        for ((start, _), pos) in ranges.into_iter().zip(pos_list) {
+            let Some((from, to)) = pattern.expand_at(&content, start, replacement, preserve_case)
+            else {
+                continue;
+            };
+            let before = lines.get(pos.row - 1).unwrap_or(&"").to_string();
+            let col_index = pos.col - 1;
+            let after = match before.get(col_index..) {
+                Some(rest) if rest.starts_with(&from) => {
+                    format!("{}{}{}", &before[..col_index], to, &rest[from.len()..])
+                }
+                // the matching spans multiple lines
+                _ => format!("{}{}", &before[..col_index.min(before.len())], to),
+            };
+            hunks.push(Hunk {
+                pos,
+                start,
+                from,
+                to,
+                before,
+                after,
+                accepted: true,
+            });
+        }
+
+        result.push(FileReplacement {
+            path: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+            content,
+            hunks,
+        });
+    }
+    return result;
+}
+
+// the result of writing replacements.
+pub struct ReplaceReport {
+    pub file_count: usize,
+    pub occurrence_count: usize,
+    // files changed on disk after previewing
+    pub skipped: Vec<PathBuf>,
+    // files failed to write and the errors
+    pub failed: Vec<(PathBuf, io::Error)>,
+    pub changed: Vec<PathBuf>,
+}
+
+// writes all accepted hunks into files, a file failed
+// to write does not stop writing the others.
+pub fn apply_replacements(root: &Path, files: &[FileReplacement]) -> ReplaceReport {
+    let mut report = ReplaceReport {
+        file_count: 0,
+        occurrence_count: 0,
+        skipped: vec![],
+        failed: vec![],
+        changed: vec![],
+    };
+    for file in files {
+        let accepted_count = file.accepted_count();
+        if accepted_count == 0 {
+            continue;
+        }
+
+        let path = root.join(&file.path);
+        if read_text_file(&path).as_ref() != Some(&file.content) {
+            report.skipped.push(file.path.clone());
+            continue;
+        }
+        if let Err(err) = write_safely(&path, file.replaced_content().as_bytes()) {
+            report.failed.push((file.path.clone(), err));
+            continue;
+        }
+
+        report.file_count += 1;
+        report.occurrence_count += accepted_count;
+        report.changed.push(file.path.clone());
+    }
+    return report;
+}
@@ -0,0 +1,99 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::{absolute_path, content_hash, state_dir, write_safely};
+
+// the buffer is written into `<state dir>/swap/<hash of path>.swp`
+// while it is modified, the file starts with the path of edited
+// file and the id of the writing process, followed by the buffer.
+const HEADER: &str = "rusditor-swap 1";
+
+// a swap file left by the last editing.
+pub struct SwapFile {
+    pub swap_path: PathBuf,
+    pub file_path: PathBuf,
+    pub content: String,
+    pub modified_time: SystemTime,
+    // the id of the process writing the swap file
+    pub pid: u32,
+}
+
+impl SwapFile {
+    // whether the writing process is still running, only
+    // available on systems with `/proc`.
+    pub fn is_owner_running(&self) -> bool {
+        Path::new("/proc").join(self.pid.to_string()).exists()
+    }
+}
+
+fn swap_path(path: &Path) -> Option<(PathBuf, String)> {
+    let path_str = absolute_path(path).to_string_lossy().to_string();
+    let name = format!("{:016x}.swp", content_hash(path_str.as_bytes()));
+    return Some((state_dir()?.join("swap").join(name), path_str));
+}
+
+pub fn write(path: &Path, buffer: &str) -> io::Result<()> {
+    let Some((swap_path, path_str)) = swap_path(path) else {
+        return Ok(());
+    };
+    let text = format!("{}\n{}\n{}\n{}", HEADER, path_str, process::id(), buffer);
+    if let Some(dir) = swap_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    return write_safely(&swap_path, text.as_bytes());
+}
+
+pub fn remove(path: &Path) {
+    if let Some((swap_path, _)) = swap_path(path) {
+        let _ = fs::remove_file(swap_path);
+    }
+}
+
+// returns the swap file of `path` not written by current process.
+pub fn find(path: &Path) -> Option<SwapFile> {
+    let (swap_path, path_str) = swap_path(path)?;
+    let text = fs::read_to_string(&swap_path).ok()?;
+    let modified_time = fs::metadata(&swap_path).and_then(|m| m.modified()).ok()?;
+
+    let mut parts = text.splitn(4, '\n');
+    if parts.next()? != HEADER || parts.next()? != path_str {
+        return None;
+    }
+    let pid = parts.next()?.parse::<u32>().ok()?;
+    if pid == process::id() {
+        return None;
+    }
+    return Some(SwapFile {
+        swap_path,
+        file_path: PathBuf::from(path_str),
+        content: parts.next().unwrap_or("").to_owned(),
+        modified_time,
+        pid,
+    });
+}
+
+// writes `buffer` into `<state dir>/recovery/<file name>.<timestamp>`,
+// or the temporary directory if the state directory is unavailable.
+pub fn dump(path: &Path, buffer: &str) -> io::Result<PathBuf> {
+    let dir = match state_dir() {
+        Some(dir) => dir.join("recovery"),
+        None => env::temp_dir(),
+    };
+    fs::create_dir_all(&dir)?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or("untitled".to_owned());
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dump_path = dir.join(format!("{}.{}", file_name, timestamp));
+    fs::write(&dump_path, buffer)?;
+    return Ok(dump_path);
+}
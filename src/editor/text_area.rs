@@ -1,10 +1,19 @@
-use std::io;
+use std::{
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use crossterm::{event::KeyCode, style::Stylize};
+use crossterm::{
+    event::KeyCode,
+    style::{ContentStyle, Stylize},
+};
 
 use crate::utils::{Cursor, Terminal};
 
-use super::direction::Direction;
+use super::{direction::Direction, theme::Theme};
+
+// the count of columns between tab stops
+static TAB_WIDTH: AtomicUsize = AtomicUsize::new(4);
 
 pub struct TextArea {
     content: String,
@@ -13,47 +22,25 @@ pub struct TextArea {
     pub margin_left: usize,
     pub margin_right: usize,
 
+    // columns scrolled out of the visible area,
+    // a tab takes the columns to the next tab stop.
     overflow_left: usize,
     overflow_right: usize,
-}
 
-pub struct TextAreaStateLeft {
-    pub is_at_left_side: bool,
-    pub is_at_area_start: bool,
-}
-pub struct TextAreaStateRight {
-    pub is_at_right_side: bool,
-    pub is_at_area_end: bool,
+    // byte range of content to highlight
+    highlight: Option<(usize, usize)>,
+    highlight_style: ContentStyle,
 }
 
 // state calculating methods
 impl TextArea {
     #[inline]
-    pub fn is_at_left_side(&self) -> io::Result<bool> {
-        return Ok(Cursor::pos_col()? == self.margin_left);
+    pub fn is_at_area_start(&self) -> io::Result<bool> {
+        return Ok(self.cursor_pos()? == 0);
     }
     #[inline]
-    pub fn is_at_right_side(&self) -> io::Result<bool> {
-        return Ok(Cursor::pos_col()? == Terminal::width() - 1);
-    }
-
-    pub fn state_left(&self) -> io::Result<TextAreaStateLeft> {
-        let is_at_left_side = self.is_at_left_side()?;
-        let is_at_area_start = is_at_left_side && self.overflow_left == 0;
-        return Ok(TextAreaStateLeft {
-            is_at_left_side,
-            is_at_area_start,
-        });
-    }
-    pub fn state_right(&self) -> io::Result<TextAreaStateRight> {
-        let cursor_pos_col = Cursor::pos_col()?;
-        let is_at_right_side = self.is_at_right_side()?;
-        let is_at_area_end = cursor_pos_col == (self.len() + self.margin_left)
-            || cursor_pos_col == (self.len() - self.overflow_left + self.margin_left);
-        return Ok(TextAreaStateRight {
-            is_at_right_side,
-            is_at_area_end,
-        });
+    pub fn is_at_area_end(&self) -> io::Result<bool> {
+        return Ok(self.cursor_pos()? == self.len());
     }
 }
 
@@ -88,16 +75,38 @@ impl TextArea {
 
     fn overflow_refresh(&mut self) {
         let visible_area_width = self.visible_area_width();
-        if self.len() > visible_area_width {
-            self.overflow_left = self.len() - visible_area_width - self.overflow_right;
+        let display_width = self.display_width();
+        if display_width > visible_area_width {
+            let max_overflow = display_width - visible_area_width;
+            self.overflow_right = self.overflow_right.min(max_overflow);
+            self.overflow_left = max_overflow - self.overflow_right;
         } else {
             self.overflow_left = 0;
             self.overflow_right = 0;
         }
     }
 
+    // moves the cursor to the column `col` of the whole text, scrolls the
+    // text if the column is out of the visible area, returns whether scrolled.
+    fn scroll_to(&mut self, col: usize) -> io::Result<bool> {
+        let visible_area_width = self.visible_area_width();
+        let max_overflow = self.display_width().saturating_sub(visible_area_width);
+        let mut overflow_left = self.overflow_left.min(max_overflow);
+        if col < overflow_left {
+            overflow_left = col;
+        } else if col > overflow_left + visible_area_width {
+            overflow_left = col - visible_area_width;
+        }
+
+        let is_scrolled = overflow_left != self.overflow_left;
+        self.overflow_left = overflow_left;
+        self.overflow_right = max_overflow - overflow_left;
+        Cursor::move_to_col(self.margin_left + col - overflow_left)?;
+        return Ok(is_scrolled);
+    }
+
     pub fn move_cursor_to_start(&mut self, rerender: bool) -> io::Result<()> {
-        if self.len() >= self.visible_area_width() {
+        if self.display_width() >= self.visible_area_width() {
             self.overflow_right += self.overflow_left;
             self.overflow_left = 0;
             if rerender {
@@ -108,7 +117,7 @@ impl TextArea {
         return Ok(());
     }
     pub fn move_cursor_to_end(&mut self, rerender: bool) -> io::Result<()> {
-        if self.len() >= self.visible_area_width() {
+        if self.display_width() >= self.visible_area_width() {
             Cursor::move_to_col(Terminal::width() - 1)?;
             self.overflow_left += self.overflow_right;
             self.overflow_right = 0;
@@ -117,50 +126,48 @@ impl TextArea {
                 self.render()?;
             }
         } else {
-            let line_end_pos = self.margin_left + self.len();
+            let line_end_pos = self.margin_left + self.display_width();
             Cursor::move_to_col(line_end_pos)?;
         }
         return Ok(());
     }
 
-    pub fn move_cursor_horizontal(&mut self, dir: Direction, rerender: bool) -> io::Result<()> {
-        match dir {
-            Direction::Left => {
-                let state = self.state_left()?;
-                if state.is_at_area_start {
-                    return Ok(());
-                }
-
-                if state.is_at_left_side {
-                    self.overflow_left -= 1;
-                    self.overflow_right += 1;
-                } else {
-                    Cursor::left(1)?;
-                    return Ok(()); // skip rerender
-                }
-            }
-            Direction::Right => {
-                let state = self.state_right()?;
-                if state.is_at_area_end {
-                    return Ok(());
-                }
-
-                if state.is_at_right_side {
-                    self.overflow_right -= 1;
-                    self.overflow_left += 1;
-                } else {
-                    Cursor::right(1)?;
-                    return Ok(()); // skip rerender
-                }
-            }
-            _ => unreachable!(),
+    // moves the cursor to the char at byte `index`,
+    // an index inside a char is snapped to its start.
+    pub fn move_cursor_to(&mut self, index: usize, rerender: bool) -> io::Result<()> {
+        let mut index = index.min(self.len());
+        while !self.content.is_char_boundary(index) {
+            index -= 1;
         }
-        if rerender {
+        if self.scroll_to(self.col_of(index))? && rerender {
             self.render()?;
         }
         return Ok(());
     }
 
+    // moves the cursor onto the start of the char under it,
+    // or to the text end if the cursor is beyond it.
+    pub fn snap_cursor(&mut self, rerender: bool) -> io::Result<()> {
+        self.move_cursor_to(self.cursor_pos()?, rerender)
+    }
+
+    // moves over one char, a tab is stepped over as a whole
+    pub fn move_cursor_horizontal(&mut self, dir: Direction, rerender: bool) -> io::Result<()> {
+        let cursor_pos = self.cursor_pos()?;
+        let target_pos = match dir {
+            Direction::Left => match self.content[..cursor_pos].chars().next_back() {
+                Some(ch) => cursor_pos - ch.len_utf8(),
+                None => return Ok(()),
+            },
+            Direction::Right => match self.content[cursor_pos..].chars().next() {
+                Some(ch) => cursor_pos + ch.len_utf8(),
+                None => return Ok(()),
+            },
+            _ => unreachable!(),
+        };
+        return self.move_cursor_to(target_pos, rerender);
+    }
+
     pub fn jump_to_word_edge(&mut self, dir: Direction, rerender: bool) -> io::Result<()> {
         let cursor_pos = self.cursor_pos()?;
         let mut displacement = match dir {
@@ -177,8 +184,8 @@ impl TextArea {
 
         // when displacement is 0 and cursor is not at left and right end
         if displacement == 0
-            && !(dir == Direction::Left && self.state_left()?.is_at_area_start)
-            && !(dir == Direction::Right && self.state_right()?.is_at_area_end)
+            && !(dir == Direction::Left && self.is_at_area_start()?)
+            && !(dir == Direction::Right && self.is_at_area_end()?)
         {
             displacement = 1;
         }
@@ -194,6 +201,107 @@ impl TextArea {
 }
 
 impl TextArea {
+    #[inline]
+    pub fn set_tab_width(width: usize) {
+        TAB_WIDTH.store(width.max(1), Ordering::Relaxed);
+    }
+
+    // the columns taken by `ch` at column `col`
+    fn char_width(ch: char, col: usize) -> usize {
+        match ch {
+            '\t' => {
+                let tab_width = TAB_WIDTH.load(Ordering::Relaxed);
+                tab_width - col % tab_width
+            }
+            _ => Self::wide_or_narrow(ch),
+        }
+    }
+
+    // wide chars of east asian scripts take two columns on terminal
+    fn wide_or_narrow(ch: char) -> usize {
+        let is_wide = matches!(ch as u32,
+            0x1100..=0x115F
+                | 0x2E80..=0x303E
+                | 0x3041..=0x33FF
+                | 0x3400..=0x4DBF
+                | 0x4E00..=0x9FFF
+                | 0xA000..=0xA4CF
+                | 0xAC00..=0xD7A3
+                | 0xF900..=0xFAFF
+                | 0xFE30..=0xFE4F
+                | 0xFF00..=0xFF60
+                | 0xFFE0..=0xFFE6
+                | 0x1F300..=0x1F64F
+                | 0x1F900..=0x1F9FF
+                | 0x20000..=0x2FFFD
+                | 0x30000..=0x3FFFD
+        );
+        return if is_wide { 2 } else { 1 };
+    }
+
+    // the part of tab-free `text` between columns `start` and `end`,
+    // wide chars cut by the bounds are replaced with spaces.
+    fn slice_columns(text: &str, start: usize, end: usize) -> String {
+        let mut sliced = String::new();
+        let mut col = 0;
+        for ch in text.chars() {
+            let width = Self::wide_or_narrow(ch);
+            if col >= end {
+                break;
+            }
+            if col >= start && col + width <= end {
+                sliced.push(ch);
+            } else if col + width > start {
+                let covered = (col + width).min(end) - col.max(start);
+                sliced.push_str(&" ".repeat(covered));
+            }
+            col += width;
+        }
+        return sliced;
+    }
+
+    // the column where the char at byte `index` starts
+    pub fn col_of(&self, index: usize) -> usize {
+        let mut col = 0;
+        for ch in self.content[..index].chars() {
+            col += Self::char_width(ch, col);
+        }
+        return col;
+    }
+
+    // the byte index of the char covering column `col`,
+    // the text length if the column is beyond the text.
+    fn index_at(&self, col: usize) -> usize {
+        let mut current_col = 0;
+        for (index, ch) in self.content.char_indices() {
+            current_col += Self::char_width(ch, current_col);
+            if col < current_col {
+                return index;
+            }
+        }
+        return self.len();
+    }
+
+    #[inline]
+    pub fn display_width(&self) -> usize {
+        self.col_of(self.len())
+    }
+
+    // the content with tabs expanded to spaces
+    fn expanded_content(&self) -> String {
+        let mut expanded = String::with_capacity(self.len());
+        let mut col = 0;
+        for ch in self.content.chars() {
+            let width = Self::char_width(ch, col);
+            match ch {
+                '\t' => expanded.push_str(&" ".repeat(width)),
+                _ => expanded.push(ch),
+            }
+            col += width;
+        }
+        return expanded;
+    }
+
     pub fn new(margin_left: usize, margin_right: usize) -> Self {
         Self {
             content: String::new(),
@@ -202,6 +310,9 @@ impl TextArea {
             overflow_left: 0,
             overflow_right: 0,
 
+            highlight: None,
+            highlight_style: ContentStyle::new(),
+
             margin_left,
             margin_right,
         }
@@ -209,39 +320,94 @@ impl TextArea {
 
     pub fn render(&self) -> io::Result<()> {
         let visible_area_width = self.visible_area_width();
-
-        let rendered_content = if self.len() == 0 && !self.placeholder.is_empty() {
-            if self.placeholder.len() > visible_area_width {
-                let rendered_range = 0..visible_area_width;
-                self.placeholder[rendered_range].dim()
-            } else {
-                self.placeholder.as_str().dim()
-            }
-        } else if self.len() > visible_area_width {
-            let rendered_range = self.overflow_left..(self.len() - self.overflow_right);
-            self.content[rendered_range].stylize()
+        let expanded_content = self.expanded_content();
+
+        let shows_placeholder = self.len() == 0 && !self.placeholder.is_empty();
+        let (text, rendered_width) = if shows_placeholder {
+            let width = self.placeholder.len().min(visible_area_width);
+            (self.placeholder[..width].to_owned(), width)
+        } else if self.display_width() > visible_area_width {
+            let rendered_end = self.display_width() - self.overflow_right;
+            let text = Self::slice_columns(&expanded_content, self.overflow_left, rendered_end);
+            (text, rendered_end - self.overflow_left)
         } else {
-            self.content.as_str().stylize()
+            (expanded_content, self.display_width())
         };
-        let remain_area_width = visible_area_width - rendered_content.content().len();
+        let rendered_content = if shows_placeholder {
+            Theme::current().hint.apply(text)
+        } else {
+            text.stylize()
+        };
+        let remain_area_width = visible_area_width.saturating_sub(rendered_width);
         let remain_space_str = " ".repeat(remain_area_width);
 
         let saved_cursor_pos = Cursor::pos_col()?;
         Cursor::move_to_col(self.margin_left)?;
-        print!("{}{}", rendered_content, remain_space_str);
+        match self.visible_highlight() {
+            Some((start, end)) if self.len() > 0 => {
+                let rendered_start = self.overflow_left;
+                let text = rendered_content.content();
+                let (start, end) = (start - rendered_start, end - rendered_start);
+                print!(
+                    "{}{}{}{}",
+                    Self::slice_columns(text, 0, start),
+                    self.highlight_style
+                        .apply(Self::slice_columns(text, start, end)),
+                    Self::slice_columns(text, end, rendered_width),
+                    remain_space_str
+                );
+            }
+            _ => print!("{}{}", rendered_content, remain_space_str),
+        }
         Cursor::move_to_col(saved_cursor_pos)?;
         return Ok(());
     }
 
+    // returns the column range of highlight clipped by visible area
+    fn visible_highlight(&self) -> Option<(usize, usize)> {
+        let (start, end) = self.highlight?;
+        // the range may be stale after the content changes
+        let (start, end) = (start.min(self.len()), end.min(self.len()));
+        if !self.content.is_char_boundary(start) || !self.content.is_char_boundary(end) {
+            return None;
+        }
+        let (start, end) = (self.col_of(start), self.col_of(end));
+        let visible_end = self.display_width() - self.overflow_right;
+        let (start, end) = (start.max(self.overflow_left), end.min(visible_end));
+        if start >= end {
+            return None;
+        }
+        return Some((start, end));
+    }
+
+    // highlights the range as search matches
+    #[inline]
+    pub fn set_highlight(&mut self, range: Option<(usize, usize)>) {
+        self.set_highlight_with(range, Theme::current().search_match);
+    }
+    #[inline]
+    pub fn set_highlight_with(&mut self, range: Option<(usize, usize)>, style: ContentStyle) {
+        self.highlight = range;
+        self.highlight_style = style;
+    }
+
     pub fn insert_char(&mut self, ch: char, rerender: bool) -> io::Result<()> {
         let insert_pos = self.cursor_pos()?;
         self.content.insert(insert_pos, ch);
 
-        if self.content.len() > self.visible_area_width() {
-            self.overflow_left += 1;
-        } else {
-            Cursor::right(1)?;
+        self.scroll_to(self.col_of(insert_pos + ch.len_utf8()))?;
+        if rerender {
+            self.render()?;
         }
+        return Ok(());
+    }
+
+    // inserts `str` without line breaks at cursor
+    pub fn insert_str(&mut self, str: &str, rerender: bool) -> io::Result<()> {
+        let insert_pos = self.cursor_pos()?;
+        self.content.insert_str(insert_pos, str);
+
+        self.scroll_to(self.col_of(insert_pos + str.len()))?;
         if rerender {
             self.render()?;
         }
@@ -249,18 +415,18 @@ impl TextArea {
     }
 
     pub fn delete_char(&mut self, rerender: bool) -> io::Result<Option<char>> {
-        if self.state_left()?.is_at_area_start {
+        if self.is_at_area_start()? {
             return Ok(None);
         }
 
-        let remove_pos = self.cursor_pos()? - 1;
-        let removed_ch = self.content.remove(remove_pos);
+        let cursor_pos = self.cursor_pos()?;
+        let Some(removed_ch) = self.content[..cursor_pos].chars().next_back() else {
+            return Ok(None);
+        };
+        let remove_pos = cursor_pos - removed_ch.len_utf8();
+        self.content.remove(remove_pos);
 
-        if self.content.len() >= self.visible_area_width() {
-            self.overflow_left -= 1;
-        } else {
-            Cursor::left(1)?;
-        }
+        self.scroll_to(self.col_of(remove_pos))?;
         if rerender {
             self.render()?;
         }
@@ -294,8 +460,16 @@ impl TextArea {
         return Ok(res_str);
     }
 
+    // the byte index of the char under cursor
     #[inline]
     pub fn cursor_pos(&self) -> io::Result<usize> {
+        let col = self.cursor_col()?;
+        return Ok(self.index_at(col));
+    }
+
+    // the column of cursor in the whole text
+    #[inline]
+    pub fn cursor_col(&self) -> io::Result<usize> {
         let value = Cursor::pos_col()? + self.overflow_left - self.margin_left;
         return Ok(value);
     }
@@ -316,3 +490,34 @@ impl TextArea {
         self.content.len()
     }
 }
+
+#[test]
+fn text_area_column_test() {
+    let mut text_area = TextArea::new(0, 0);
+    text_area.content = String::from("a\tbc\t\td");
+    assert_eq!(text_area.display_width(), 13);
+    assert_eq!(text_area.expanded_content(), "a   bc      d");
+    assert_eq!(text_area.col_of(1), 1);
+    assert_eq!(text_area.col_of(2), 4);
+    assert_eq!(text_area.col_of(5), 8);
+    // the columns inside a tab belong to the tab
+    assert_eq!(text_area.index_at(2), 1);
+    assert_eq!(text_area.index_at(4), 2);
+    assert_eq!(text_area.index_at(7), 4);
+    assert_eq!(text_area.index_at(11), 5);
+    assert_eq!(text_area.index_at(20), 7);
+
+    // non-ascii chars take columns by display width, not by bytes
+    text_area.content = String::from("é\t中x");
+    assert_eq!(text_area.expanded_content(), "é   中x");
+    assert_eq!(text_area.display_width(), 7);
+    assert_eq!(text_area.col_of(2), 1);
+    assert_eq!(text_area.col_of(3), 4);
+    assert_eq!(text_area.col_of(6), 6);
+    assert_eq!(text_area.index_at(5), 3);
+    assert_eq!(text_area.index_at(6), 6);
+    // wide chars cut by the bounds are replaced with spaces
+    assert_eq!(TextArea::slice_columns("a中b", 2, 4), " b");
+    assert_eq!(TextArea::slice_columns("a中b", 0, 2), "a ");
+    assert_eq!(TextArea::slice_columns("a中b", 1, 3), "中");
+}
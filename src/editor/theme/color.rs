@@ -0,0 +1,146 @@
+use std::env;
+
+use crossterm::style::Color;
+
+use super::super::config::ConfigValue;
+
+const NAMED_COLORS: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("dark_grey", Color::DarkGrey),
+    ("red", Color::Red),
+    ("dark_red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark_green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark_yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark_blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark_magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark_cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+];
+
+// parses a color name like `"dark_blue"`, a hex color
+// like `"#1e1e2e"` or an index of the 256-color palette.
+pub fn parse_color(value: &ConfigValue) -> Result<Color, String> {
+    let name = match value {
+        ConfigValue::Integer(n) => {
+            return match u8::try_from(*n) {
+                Ok(index) => Ok(Color::AnsiValue(index)),
+                Err(_) => Err(format!(
+                    "color index must be between 0 and 255, found {}",
+                    n
+                )),
+            };
+        }
+        ConfigValue::String(name) => name.as_str(),
+        _ => return Err(format!("expected a color, found {}", value.type_name())),
+    };
+
+    if let Ok(index) = name.parse::<u8>() {
+        return Ok(Color::AnsiValue(index));
+    }
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        if hex.len() == 6 && hex.is_ascii() {
+            if let (Some(r), Some(g), Some(b)) = (channel(0), channel(2), channel(4)) {
+                return Ok(Color::Rgb { r, g, b });
+            }
+        }
+        return Err(format!("invalid hex color `{}`, expected `#RRGGBB`", name));
+    }
+    let lowercase = name.to_ascii_lowercase();
+    if let Some((_, color)) = NAMED_COLORS.iter().find(|(n, _)| *n == lowercase) {
+        return Ok(*color);
+    }
+    return Err(format!(
+        "unknown color `{}`, expected `#RRGGBB`, 0-255 or one of: {}",
+        name,
+        NAMED_COLORS.map(|(n, _)| n).join(", ")
+    ));
+}
+
+// whether the terminal claims to support 24-bit colors
+pub fn supports_truecolor() -> bool {
+    let value = env::var("COLORTERM").unwrap_or_default();
+    return value == "truecolor" || value == "24bit";
+}
+
+// --- --- --- --- --- ---
+
+// the levels of each channel in the 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    return d(r1, r2) + d(g1, g2) + d(b1, b2);
+}
+
+// finds the closest color in the 256-color palette,
+// either from the color cube or the grayscale ramp.
+fn closest_ansi_value(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |v: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+            .unwrap()
+    };
+    let (ri, gi, bi) = (cube_index(rgb.0), cube_index(rgb.1), cube_index(rgb.2));
+    let cube_rgb = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_value = (16 + 36 * ri + 6 * gi + bi) as u8;
+
+    // grays from 232 to 255 are 8, 18, ..., 238
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + gray_index * 10;
+    let gray_value = 232 + gray_index;
+
+    match distance(rgb, (gray, gray, gray)) < distance(rgb, cube_rgb) {
+        true => gray_value,
+        false => cube_value,
+    }
+}
+
+// replaces RGB colors for terminals without truecolor support
+pub fn fallback_color(color: Color) -> Color {
+    match color {
+        Color::Rgb { r, g, b } => Color::AnsiValue(closest_ansi_value((r, g, b))),
+        color => color,
+    }
+}
+
+#[test]
+fn parse_color_test() {
+    let parse = |value: &str| parse_color(&ConfigValue::String(value.to_owned()));
+    assert_eq!(parse("dark_blue"), Ok(Color::DarkBlue));
+    assert_eq!(parse("Grey"), Ok(Color::Grey));
+    assert_eq!(
+        parse("#1E90ff"),
+        Ok(Color::Rgb {
+            r: 0x1e,
+            g: 0x90,
+            b: 0xff
+        })
+    );
+    assert!(parse("#1e90f").is_err());
+    assert!(parse("#1e90fg").is_err());
+    assert!(parse("orange").is_err());
+    assert_eq!(parse("27"), Ok(Color::AnsiValue(27)));
+    assert_eq!(
+        parse_color(&ConfigValue::Integer(208)),
+        Ok(Color::AnsiValue(208))
+    );
+    assert!(parse_color(&ConfigValue::Integer(256)).is_err());
+    assert!(parse_color(&ConfigValue::Boolean(true)).is_err());
+
+    let fallback = |r, g, b| fallback_color(Color::Rgb { r, g, b });
+    assert_eq!(fallback(0, 0, 0), Color::AnsiValue(16));
+    assert_eq!(fallback(255, 0, 0), Color::AnsiValue(196));
+    assert_eq!(fallback(0x30, 0x30, 0x30), Color::AnsiValue(236));
+    assert_eq!(fallback(255, 255, 255), Color::AnsiValue(231));
+    assert_eq!(fallback(0xd7, 0x87, 0x00), Color::AnsiValue(172));
+    assert_eq!(fallback_color(Color::Red), Color::Red);
+}
@@ -0,0 +1,444 @@
+mod color;
+
+pub use color::parse_color;
+
+use std::{env, fs, path::PathBuf, sync::OnceLock};
+
+use crossterm::style::{Attribute, Color, ContentStyle};
+
+use crate::utils::{config_dir, Terminal};
+
+use super::config::{toml, ConfigError, ConfigValue};
+
+// the styles of every part of the editor, a theme file sets
+// the styles with sections like `[title]` and `[syntax.keyword]`.
+pub struct Theme {
+    pub title: ContentStyle,
+    // the exit button at the end of title
+    pub title_button: ContentStyle,
+    pub border: ContentStyle,
+    pub line_label: ContentStyle,
+    pub line_label_active: ContentStyle,
+    pub dashboard: ContentStyle,
+    // the editor state and cursor position
+    pub dashboard_state: ContentStyle,
+    pub message_info: ContentStyle,
+    pub message_warning: ContentStyle,
+    pub message_error: ContentStyle,
+    // the prompt and the button of components
+    pub prompt: ContentStyle,
+    // the selected row of lists
+    pub selection: ContentStyle,
+    pub search_match: ContentStyle,
+    // the bracket matching the one at cursor
+    pub bracket_match: ContentStyle,
+    // placeholders and status texts
+    pub hint: ContentStyle,
+    pub diff_added: ContentStyle,
+    pub diff_removed: ContentStyle,
+    // styles of syntax scopes like `keyword` and `comment`
+    pub syntax: Vec<(String, ContentStyle)>,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+impl Background {
+    // reads `COLORFGBG` set by some terminals like `15;0`,
+    // or asks the terminal for its background color.
+    pub fn detect() -> Self {
+        if let Ok(value) = env::var("COLORFGBG") {
+            if let Some(Ok(index)) = value.rsplit(';').next().map(str::parse::<u8>) {
+                return match index {
+                    7 | 15 => Self::Light,
+                    _ => Self::Dark,
+                };
+            }
+        }
+        return match Terminal::background_color() {
+            Some(rgb) => Self::from_rgb(rgb),
+            None => Self::Dark,
+        };
+    }
+
+    fn from_rgb((r, g, b): (u8, u8, u8)) -> Self {
+        let luminance = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+        match luminance > 128 * 1000 {
+            true => Self::Light,
+            false => Self::Dark,
+        }
+    }
+}
+
+fn style(fg: Option<Color>, bg: Option<Color>, attributes: &[Attribute]) -> ContentStyle {
+    let mut style = ContentStyle::new();
+    style.foreground_color = fg;
+    style.background_color = bg;
+    for attribute in attributes {
+        style.attributes.set(*attribute);
+    }
+    return style;
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        use Color::*;
+        let accent = style(Some(White), Some(Red), &[]);
+        let syntax = [
+            ("keyword", Magenta),
+            ("type", Yellow),
+            ("function", Blue),
+            ("string", Green),
+            ("number", Cyan),
+            ("comment", DarkGrey),
+        ];
+        Self {
+            title: style(Some(Black), Some(White), &[Attribute::Bold]),
+            title_button: accent,
+            border: style(None, Some(White), &[]),
+            line_label: style(Some(DarkGrey), Some(Grey), &[]),
+            line_label_active: style(Some(Black), Some(White), &[Attribute::Bold]),
+            dashboard: style(None, Some(White), &[]),
+            dashboard_state: accent,
+            message_info: style(Some(Black), Some(White), &[]),
+            message_warning: style(Some(Black), Some(Yellow), &[]),
+            message_error: style(Some(White), Some(Red), &[]),
+            prompt: style(Some(Black), Some(White), &[Attribute::Bold]),
+            selection: style(Some(Black), Some(White), &[]),
+            search_match: style(None, None, &[Attribute::Reverse]),
+            bracket_match: style(
+                Some(Yellow),
+                None,
+                &[Attribute::Bold, Attribute::Underlined],
+            ),
+            hint: style(None, None, &[Attribute::Dim]),
+            diff_added: style(Some(Green), None, &[]),
+            diff_removed: style(Some(Red), None, &[]),
+            syntax: syntax
+                .map(|(scope, fg)| (scope.to_owned(), style(Some(fg), None, &[])))
+                .to_vec(),
+        }
+    }
+}
+
+impl Theme {
+    // the default theme for terminals with light background,
+    // bars are drawn in dark grey instead of white.
+    pub fn light() -> Self {
+        use Color::*;
+        let bar = style(Some(White), Some(DarkGrey), &[]);
+        let syntax = [
+            ("keyword", DarkMagenta),
+            ("type", DarkYellow),
+            ("function", DarkBlue),
+            ("string", DarkGreen),
+            ("number", DarkCyan),
+            ("comment", DarkGrey),
+        ];
+        Self {
+            title: style(Some(White), Some(DarkGrey), &[Attribute::Bold]),
+            border: style(None, Some(DarkGrey), &[]),
+            line_label: style(Some(Black), Some(Grey), &[]),
+            line_label_active: style(Some(White), Some(DarkGrey), &[Attribute::Bold]),
+            dashboard: style(None, Some(DarkGrey), &[]),
+            message_info: bar,
+            prompt: style(Some(White), Some(DarkGrey), &[Attribute::Bold]),
+            selection: bar,
+            bracket_match: style(
+                Some(DarkBlue),
+                None,
+                &[Attribute::Bold, Attribute::Underlined],
+            ),
+            diff_added: style(Some(DarkGreen), None, &[]),
+            diff_removed: style(Some(DarkRed), None, &[]),
+            syntax: syntax
+                .map(|(scope, fg)| (scope.to_owned(), style(Some(fg), None, &[])))
+                .to_vec(),
+            ..Self::default()
+        }
+    }
+
+    // the theme without colors, for `NO_COLOR` and `--no-color`
+    pub fn monochrome() -> Self {
+        let plain = ContentStyle::new();
+        let reverse = style(None, None, &[Attribute::Reverse]);
+        let strong = style(None, None, &[Attribute::Reverse, Attribute::Bold]);
+        Self {
+            title: reverse,
+            title_button: strong,
+            border: reverse,
+            line_label: plain,
+            line_label_active: strong,
+            dashboard: reverse,
+            dashboard_state: strong,
+            message_info: reverse,
+            message_warning: strong,
+            message_error: strong,
+            prompt: strong,
+            selection: reverse,
+            search_match: reverse,
+            bracket_match: style(None, None, &[Attribute::Bold, Attribute::Underlined]),
+            hint: style(None, None, &[Attribute::Dim]),
+            diff_added: style(None, None, &[Attribute::Bold]),
+            diff_removed: plain,
+            syntax: vec![(
+                String::from("keyword"),
+                style(None, None, &[Attribute::Bold]),
+            )],
+        }
+    }
+
+    const ELEMENTS: [&'static str; 17] = [
+        "title",
+        "title.button",
+        "border",
+        "line_label",
+        "line_label.active",
+        "dashboard",
+        "dashboard.state",
+        "message.info",
+        "message.warning",
+        "message.error",
+        "prompt",
+        "selection",
+        "search_match",
+        "bracket_match",
+        "hint",
+        "diff.added",
+        "diff.removed",
+    ];
+
+    // the theme used to draw the editor,
+    // the default one if none is installed.
+    #[inline]
+    pub fn current() -> &'static Theme {
+        THEME.get_or_init(Theme::default)
+    }
+
+    // sets the theme used to draw, only the first call takes effect.
+    #[inline]
+    pub fn install(self) {
+        let _ = THEME.set(self);
+    }
+
+    // loads the built-in theme `dark`, `light` or `monochrome`, or
+    // the theme named `name` from `$XDG_CONFIG_HOME/rusditor/themes`,
+    // or from a path ending with `.toml`. `default` and theme files
+    // are based on the theme for the background of terminal.
+    // `accent` overrides the background of accent parts, and RGB
+    // colors fall back to the 256-color palette without truecolor support.
+    pub fn load(name: &str, accent: Option<Color>) -> (Self, Vec<ConfigError>) {
+        let mut theme = match name {
+            "dark" => Self::default(),
+            "light" => Self::light(),
+            "monochrome" => Self::monochrome(),
+            _ => match Background::detect() {
+                Background::Dark => Self::default(),
+                Background::Light => Self::light(),
+            },
+        };
+        let mut errors = vec![];
+        if !["default", "dark", "light", "monochrome"].contains(&name) {
+            let path = match name.ends_with(".toml") {
+                true => Some(PathBuf::from(name)),
+                false => config_dir().map(|dir| dir.join("themes").join(format!("{}.toml", name))),
+            };
+            match path.map(|path| (fs::read_to_string(&path), path)) {
+                Some((Ok(text), path)) => {
+                    errors = theme.parse(&text, &path.display().to_string());
+                }
+                Some((Err(err), path)) => errors.push(ConfigError {
+                    file: path.display().to_string(),
+                    line: None,
+                    message: format!("cannot read theme `{}`: {}", name, err),
+                }),
+                None => errors.push(ConfigError {
+                    file: String::from("config.toml"),
+                    line: None,
+                    message: format!("cannot find theme `{}`", name),
+                }),
+            }
+        }
+
+        if let Some(accent) = accent {
+            theme.title_button.background_color = Some(accent);
+            theme.dashboard_state.background_color = Some(accent);
+        }
+        if !color::supports_truecolor() {
+            theme.fallback_colors();
+        }
+        return (theme, errors);
+    }
+
+    // applies the entries of a theme file over the current styles
+    fn parse(&mut self, text: &str, file_name: &str) -> Vec<ConfigError> {
+        let (entries, mut errors) = toml::parse(text, file_name);
+        for entry in entries {
+            if let Err(message) = self.set(&entry.key, &entry.value) {
+                errors.push(ConfigError {
+                    file: file_name.to_owned(),
+                    line: Some(entry.line),
+                    message,
+                });
+            }
+        }
+        errors.sort_by_key(|err| err.line);
+        return errors;
+    }
+
+    fn element_mut(&mut self, name: &str) -> Option<&mut ContentStyle> {
+        let style = match name {
+            "title" => &mut self.title,
+            "title.button" => &mut self.title_button,
+            "border" => &mut self.border,
+            "line_label" => &mut self.line_label,
+            "line_label.active" => &mut self.line_label_active,
+            "dashboard" => &mut self.dashboard,
+            "dashboard.state" => &mut self.dashboard_state,
+            "message.info" => &mut self.message_info,
+            "message.warning" => &mut self.message_warning,
+            "message.error" => &mut self.message_error,
+            "prompt" => &mut self.prompt,
+            "selection" => &mut self.selection,
+            "search_match" => &mut self.search_match,
+            "bracket_match" => &mut self.bracket_match,
+            "hint" => &mut self.hint,
+            "diff.added" => &mut self.diff_added,
+            "diff.removed" => &mut self.diff_removed,
+            _ => {
+                let scope = name.strip_prefix("syntax.")?;
+                let index = match self.syntax.iter().position(|(s, _)| s == scope) {
+                    Some(index) => index,
+                    None => {
+                        self.syntax.push((scope.to_owned(), ContentStyle::new()));
+                        self.syntax.len() - 1
+                    }
+                };
+                &mut self.syntax[index].1
+            }
+        };
+        return Some(style);
+    }
+
+    // sets a property like `title.fg` or `syntax.comment.italic`
+    fn set(&mut self, key: &str, value: &ConfigValue) -> Result<(), String> {
+        let Some((element, property)) = key.rsplit_once('.') else {
+            return Err(format!("unknown theme option `{}`", key));
+        };
+        let Some(style) = self.element_mut(element) else {
+            return Err(format!(
+                "unknown theme element `{}`, expected `syntax.<scope>` or one of: {}",
+                element,
+                Self::ELEMENTS.join(", ")
+            ));
+        };
+
+        let attribute = match property {
+            "fg" => {
+                style.foreground_color = Some(parse_color(value)?);
+                return Ok(());
+            }
+            "bg" => {
+                style.background_color = Some(parse_color(value)?);
+                return Ok(());
+            }
+            "bold" => Attribute::Bold,
+            "dim" => Attribute::Dim,
+            "italic" => Attribute::Italic,
+            "underline" => Attribute::Underlined,
+            "reverse" => Attribute::Reverse,
+            _ => {
+                return Err(format!(
+                    "unknown style property `{}` of `{}`",
+                    property, element
+                ))
+            }
+        };
+        let ConfigValue::Boolean(enabled) = value else {
+            return Err(format!(
+                "`{}` expects a boolean, found {}",
+                key,
+                value.type_name()
+            ));
+        };
+        match enabled {
+            true => style.attributes.set(attribute),
+            false => style.attributes.unset(attribute),
+        }
+        return Ok(());
+    }
+
+    fn fallback_colors(&mut self) {
+        let fallback = |style: &mut ContentStyle| {
+            style.foreground_color = style.foreground_color.map(color::fallback_color);
+            style.background_color = style.background_color.map(color::fallback_color);
+        };
+        for name in Self::ELEMENTS {
+            if let Some(style) = self.element_mut(name) {
+                fallback(style);
+            }
+        }
+        for (_, style) in &mut self.syntax {
+            fallback(style);
+        }
+    }
+}
+
+#[test]
+fn theme_parse_test() {
+    let text = "\
+[title]
+fg = \"#282828\"
+bg = 252
+italic = true
+bold = false
+
+[syntax.comment]
+fg = \"dark_grey\"
+italic = true
+
+[syntax.macro]
+fg = \"cyan\"
+
+[line_label]
+fg = \"orange\"
+blink = true
+[statusbar]
+bg = \"white\"
+";
+    let mut theme = Theme::default();
+    let errors = theme.parse(text, "dark.toml");
+    assert_eq!(
+        theme.title.foreground_color,
+        Some(Color::Rgb {
+            r: 0x28,
+            g: 0x28,
+            b: 0x28
+        })
+    );
+    assert_eq!(theme.title.background_color, Some(Color::AnsiValue(252)));
+    assert!(theme.title.attributes.has(Attribute::Italic));
+    assert!(!theme.title.attributes.has(Attribute::Bold));
+    assert!(theme
+        .syntax
+        .iter()
+        .any(|(scope, style)| scope == "comment" && style.attributes.has(Attribute::Italic)));
+    assert!(theme.syntax.iter().any(|(scope, _)| scope == "macro"));
+
+    let lines: Vec<Option<usize>> = errors.iter().map(|err| err.line).collect();
+    assert_eq!(lines, vec![Some(15), Some(16), Some(18)]);
+    assert!(errors[0]
+        .to_string()
+        .starts_with("dark.toml:15: unknown color `orange`"));
+
+    theme.fallback_colors();
+    assert_eq!(theme.title.foreground_color, Some(Color::AnsiValue(235)));
+
+    assert_eq!(Background::from_rgb((0xfd, 0xf6, 0xe3)), Background::Light);
+    assert_eq!(Background::from_rgb((0x28, 0x2c, 0x34)), Background::Dark);
+}
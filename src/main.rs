@@ -3,41 +3,112 @@
 mod editor;
 mod utils;
 
-use std::{io, path::Path};
+use std::{
+    any::Any,
+    env, io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process,
+};
 
 use clap::Parser;
 
-use editor::Editor;
+use editor::{ConfigValue, Editor, EditorConfig, Theme};
+use utils::Terminal;
 
 #[derive(Parser, Debug)]
 #[command(name="Rusditor", version)]
 struct Args {
     file_path: Option<String>,
-    #[arg(short, long, long_help="Set accent color for this editor; Options: [red, blue, dark_red, dark_blue, dark_grey, dark_cyan, dark_yellow, dark_magenta]")]
+    #[arg(short, long, long_help="Set accent color for this editor; Accepts a color name like `dark_blue`, `#RRGGBB` or a 256-color index")]
     accent_color: Option<String>,
+    #[arg(short, long, long_help="Use a theme in `$XDG_CONFIG_HOME/rusditor/themes`, or a theme file ending with `.toml`")]
+    theme: Option<String>,
+    #[arg(short, long = "set", value_name = "KEY=VALUE", long_help="Override an option of config file, e.g. `--set tab_width=2`; can be used multiple times")]
+    set: Vec<String>,
+    #[arg(long, long_help="Draw without colors, only reverse video and bold; Also enabled by the NO_COLOR environment variable")]
+    no_color: bool,
+}
+
+// restores the terminal before the panic message is printed.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = Terminal::restore();
+        default_hook(info);
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    return String::from("unknown panic");
 }
 
 fn main() -> io::Result<()> {
-    let mut editor = Editor::new();
+    install_panic_hook();
+
     let args = Args::parse();
-    if let Some(path) = args.file_path {
-        let file_path = Path::new(&path);
-        if file_path.exists() {
-            editor.read_file(&path)?;
-        }
-    }
+
+    // command line options override the config file
+    let (mut config, mut config_errors) = EditorConfig::load();
+    let mut arg_results = vec![];
     if let Some(color) = args.accent_color {
-        Editor::set_accent_color(&color);
+        arg_results.push(config.set("accent_color", &ConfigValue::String(color)));
+    }
+    if let Some(theme) = args.theme {
+        arg_results.push(config.set("theme", &ConfigValue::String(theme)));
+    }
+    for arg in &args.set {
+        arg_results.push(config.set_from_arg(arg));
+    }
+    for result in arg_results {
+        if let Err(message) = result {
+            eprintln!("error: {}", message);
+            process::exit(2);
+        }
     }
+    // https://no-color.org
+    let no_color = args.no_color || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let (theme, theme_errors) = match no_color {
+        true => (Theme::monochrome(), vec![]),
+        false => Theme::load(&config.theme, config.accent_color),
+    };
+    theme.install();
+    config_errors.extend(theme_errors);
 
-    if cfg!(debug_assertions) {
-        // debug mode: show `io::Error` message
+    let mut editor = Editor::new(config);
+
+    let run_res = panic::catch_unwind(AssertUnwindSafe(|| -> io::Result<()> {
+        if let Some(path) = args.file_path {
+            let file_path = Path::new(&path);
+            if file_path.exists() {
+                editor.read_file(&path)?;
+            }
+        }
         editor.init()?;
+        editor.report_config_errors(&config_errors)?;
         editor.cycle()?;
-    } else {
-        // release mode: exit directly
-        editor.init().or_else(|_| editor.close())?;
-        editor.cycle().or_else(|_| editor.close())?;
+        return Ok(());
+    }));
+
+    let error_message = match run_res {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(err)) => err.to_string(),
+        Err(payload) => panic_message(payload.as_ref()),
+    };
+
+    // exit because of error, keep the unsaved text
+    let _ = Terminal::restore();
+    eprintln!("Rusditor exited because of an error: {}", error_message);
+    match editor.dump_buffer() {
+        Ok(Some(path)) => eprintln!("Current text is saved to {}", path.display()),
+        Ok(None) => {}
+        Err(err) => eprintln!("Failed to save current text: {}", err),
     }
-    return Ok(());
+    process::exit(1);
 }
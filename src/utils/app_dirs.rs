@@ -0,0 +1,35 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+const APP_NAME: &str = "rusditor";
+
+// returns `$XDG_STATE_HOME/rusditor`,
+// or `~/.local/state/rusditor` if the variable is not set.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    return Some(base.join(APP_NAME));
+}
+
+// returns `$XDG_CONFIG_HOME/rusditor`,
+// or `~/.config/rusditor` if the variable is not set.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    return Some(base.join(APP_NAME));
+}
+
+// returns the canonical path, or the path joined to
+// current directory if the file does not exist.
+pub fn absolute_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| match env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.to_path_buf(),
+    })
+}
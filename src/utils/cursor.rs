@@ -37,12 +37,6 @@ impl Cursor {
     pub fn down(cell: usize) -> io::Result<()> {
         execute!(io::stdout(), cursor::MoveDown(cell as u16))
     }
-    pub fn left(cell: usize) -> io::Result<()> {
-        execute!(io::stdout(), cursor::MoveLeft(cell as u16))
-    }
-    pub fn right(cell: usize) -> io::Result<()> {
-        execute!(io::stdout(), cursor::MoveRight(cell as u16))
-    }
 
     pub fn save_pos() -> io::Result<()> {
         execute!(io::stdout(), cursor::SavePosition)
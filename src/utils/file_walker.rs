@@ -0,0 +1,72 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::gitignore::GitIgnore;
+
+// recursively collects files under `root`,
+// skips `.git` directory and paths ignored by `.gitignore`.
+pub fn walk_files(root: &Path) -> Vec<PathBuf> {
+    let mut result = vec![];
+    let mut ignores = vec![];
+    walk_dir(root, &mut ignores, &mut result);
+    return result;
+}
+
+fn walk_dir(dir: &Path, ignores: &mut Vec<(PathBuf, GitIgnore)>, result: &mut Vec<PathBuf>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    let has_ignore_file = match GitIgnore::from_dir(dir) {
+        Some(ignore) => {
+            ignores.push((dir.to_path_buf(), ignore));
+            true
+        }
+        None => false,
+    };
+
+    let mut entries = read_dir
+        .filter_map(|entry| entry.ok())
+        .collect::<Vec<fs::DirEntry>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_dir = file_type.is_dir();
+        if (is_dir && entry.file_name() == ".git") || is_ignored(&path, is_dir, ignores) {
+            continue;
+        }
+
+        if is_dir {
+            walk_dir(&path, ignores, result);
+        } else if file_type.is_file() {
+            result.push(path);
+        }
+    }
+
+    if has_ignore_file {
+        ignores.pop();
+    }
+}
+
+fn is_ignored(path: &Path, is_dir: bool, ignores: &[(PathBuf, GitIgnore)]) -> bool {
+    // rules in deeper `.gitignore` take precedence
+    for (base, ignore) in ignores.iter().rev() {
+        let Ok(rel_path) = path.strip_prefix(base) else {
+            continue;
+        };
+        let rel_path = rel_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if let Some(is_ignored) = ignore.is_ignored(&rel_path, is_dir) {
+            return is_ignored;
+        }
+    }
+    return false;
+}
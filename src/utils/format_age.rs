@@ -0,0 +1,12 @@
+use std::time::SystemTime;
+
+// formats the time passed since `time`, like `5m ago`.
+pub fn format_age(time: SystemTime) -> String {
+    let seconds = time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    match seconds {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 60 * 60 => format!("{}m ago", s / 60),
+        s if s < 60 * 60 * 24 => format!("{}h ago", s / 60 / 60),
+        s => format!("{}d ago", s / 60 / 60 / 24),
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fs, path::Path};
+
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    // pattern containing `/` is matched against the whole
+    // relative path, otherwise against the file name.
+    anchored: bool,
+}
+
+// rules parsed from a single `.gitignore` file.
+pub struct GitIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+impl GitIgnore {
+    pub const FILE_NAME: &'static str = ".gitignore";
+
+    pub fn parse(content: &str) -> Self {
+        let mut rules = vec![];
+        for line in content.lines() {
+            let mut line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let negated = line.starts_with('!');
+            if negated {
+                line = &line[1..];
+            }
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            let anchored = line.contains('/');
+            let pattern = line.trim_start_matches('/').to_owned();
+            if pattern.is_empty() {
+                continue;
+            }
+            rules.push(IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+            });
+        }
+        return Self { rules };
+    }
+
+    // reads `.gitignore` in `dir` if it exists.
+    pub fn from_dir(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(Self::FILE_NAME)).ok()?;
+        return Some(Self::parse(&content));
+    }
+
+    // `rel_path` is the path relative to the directory of this
+    // `.gitignore`, separated by `/`.
+    // returns None when no rule matches the path,
+    // otherwise returns whether the path is ignored.
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> Option<bool> {
+        let file_name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        // the last matching rule takes effect
+        for rule in self.rules.iter().rev() {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let target = if rule.anchored { rel_path } else { file_name };
+            if glob_match(rule.pattern.as_bytes(), target.as_bytes()) {
+                return Some(!rule.negated);
+            }
+        }
+        return None;
+    }
+}
+
+// supports `*`, `**`, `?` and `[...]` character class.
+pub fn glob_match(pat: &[u8], text: &[u8]) -> bool {
+    match pat.first() {
+        None => text.is_empty(),
+        Some(b'*') if pat.get(1) == Some(&b'*') => {
+            // `**/` also matches zero directories
+            let rest = &pat[2..];
+            if let Some(rest_without_slash) = rest.strip_prefix(b"/") {
+                if glob_match(rest_without_slash, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        Some(b'*') => {
+            let rest = &pat[1..];
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => match text.first() {
+            Some(&ch) if ch != b'/' => glob_match(&pat[1..], &text[1..]),
+            _ => false,
+        },
+        Some(b'[') => {
+            let Some(close) = pat.iter().skip(1).position(|&ch| ch == b']') else {
+                return text.first() == Some(&b'[') && glob_match(&pat[1..], &text[1..]);
+            };
+            let Some(&ch) = text.first() else {
+                return false;
+            };
+            let mut class = &pat[1..close + 1];
+            let negated = class.first() == Some(&b'!') || class.first() == Some(&b'^');
+            if negated {
+                class = &class[1..];
+            }
+            let mut is_matched = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == b'-' {
+                    is_matched |= class[i] <= ch && ch <= class[i + 2];
+                    i += 3;
+                } else {
+                    is_matched |= class[i] == ch;
+                    i += 1;
+                }
+            }
+            is_matched != negated && glob_match(&pat[close + 2..], &text[1..])
+        }
+        Some(b'\\') if pat.len() > 1 => {
+            text.first() == Some(&pat[1]) && glob_match(&pat[2..], &text[1..])
+        }
+        Some(&ch) => text.first() == Some(&ch) && glob_match(&pat[1..], &text[1..]),
+    }
+}
+
+#[test]
+fn gitignore_test() {
+    let ignore = GitIgnore::parse(
+        "# comment\n*.log\ntarget/\n/build\ndocs/**/*.tmp\n!keep.log\nfile[0-9].txt\n",
+    );
+
+    assert_eq!(ignore.is_ignored("a.log", false), Some(true));
+    assert_eq!(ignore.is_ignored("src/a.log", false), Some(true));
+    assert_eq!(ignore.is_ignored("keep.log", false), Some(false));
+    assert_eq!(ignore.is_ignored("target", true), Some(true));
+    assert_eq!(ignore.is_ignored("target", false), None);
+    assert_eq!(ignore.is_ignored("build", true), Some(true));
+    assert_eq!(ignore.is_ignored("src/build", true), None);
+    assert_eq!(ignore.is_ignored("docs/a.tmp", false), Some(true));
+    assert_eq!(ignore.is_ignored("docs/x/y/a.tmp", false), Some(true));
+    assert_eq!(ignore.is_ignored("file1.txt", false), Some(true));
+    assert_eq!(ignore.is_ignored("fileA.txt", false), None);
+    assert_eq!(ignore.is_ignored("src/main.rs", false), None);
+}
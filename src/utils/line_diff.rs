@@ -0,0 +1,100 @@
+#[derive(Debug, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+// the max size of LCS table (about 6 MB), larger
+// differences are not compared line by line.
+const MAX_TABLE_SIZE: usize = 1_600_000;
+
+// returns the line-based difference from `old` to `new`,
+// `None` if they differ in too many lines to compare.
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Option<Vec<DiffLine<'a>>> {
+    let old_lines = old.lines().collect::<Vec<&str>>();
+    let new_lines = new.lines().collect::<Vec<&str>>();
+
+    // skip the common head and tail
+    let head = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let tail = old_lines[head..]
+        .iter()
+        .rev()
+        .zip(new_lines[head..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old_lines[head..old_lines.len() - tail];
+    let new_middle = &new_lines[head..new_lines.len() - tail];
+
+    let (n, m) = (old_middle.len(), new_middle.len());
+    if (n + 1) * (m + 1) > MAX_TABLE_SIZE {
+        return None;
+    }
+
+    let mut result = old_lines[..head]
+        .iter()
+        .map(|l| DiffLine::Same(l))
+        .collect::<Vec<DiffLine>>();
+
+    // lcs[i][j] is the LCS length of old_middle[i..] and new_middle[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_middle[i] == new_middle[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_middle[i] == new_middle[j] {
+            result.push(DiffLine::Same(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            result.push(DiffLine::Removed(old_middle[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(new_middle[j]));
+            j += 1;
+        }
+    }
+
+    result.extend(
+        old_lines[old_lines.len() - tail..]
+            .iter()
+            .map(|l| DiffLine::Same(l)),
+    );
+    return Some(result);
+}
+
+#[test]
+fn line_diff_test() {
+    use DiffLine::*;
+
+    assert_eq!(
+        line_diff("a\nb\nc\nd", "a\nc\nx\nd"),
+        Some(vec![
+            Same("a"),
+            Removed("b"),
+            Same("c"),
+            Added("x"),
+            Same("d")
+        ])
+    );
+    assert_eq!(line_diff("", "a"), Some(vec![Added("a")]));
+    assert_eq!(line_diff("a\nb", "a\nb"), Some(vec![Same("a"), Same("b")]));
+
+    // too many different lines are not compared
+    let old = (0..2000).map(|i| format!("a{}\n", i)).collect::<String>();
+    let new = (0..2000).map(|i| format!("b{}\n", i)).collect::<String>();
+    assert_eq!(line_diff(&old, &new), None);
+    assert!(line_diff(&old, &old).is_some());
+}
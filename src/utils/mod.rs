@@ -1,13 +1,28 @@
+mod app_dirs;
+mod content_hash;
+mod file_walker;
+mod format_age;
+mod gitignore;
+mod line_diff;
 mod logger;
 mod loop_traverser;
 mod number_bit_count;
+mod safe_write;
 
 pub mod cursor;
 pub mod terminal;
 
+#[allow(unused_imports)]
 pub use logger::log;
+pub use app_dirs::{absolute_path, config_dir, state_dir};
+pub use content_hash::content_hash;
+pub use file_walker::walk_files;
+pub use format_age::format_age;
+pub use gitignore::glob_match;
+pub use line_diff::{line_diff, DiffLine};
 pub use number_bit_count::number_bit_count;
+pub use safe_write::write_safely;
 
 pub use cursor::Cursor;
 pub use loop_traverser::LoopTraverser;
-pub use terminal::Terminal;
+pub use terminal::{Terminal, TerminalInput};
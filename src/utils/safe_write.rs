@@ -0,0 +1,29 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+// writes into a temporary file beside the target and renames it
+// to the target, so that the target file is never half-written.
+pub fn write_safely(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file path"))?;
+    let temp_path = path.with_file_name(format!(".{}.rusditor-tmp", file_name.to_string_lossy()));
+
+    let write_res = (|| {
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(bytes)?;
+        temp_file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, path)
+    })();
+
+    if write_res.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    return write_res;
+}
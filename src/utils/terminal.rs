@@ -1,14 +1,35 @@
-use std::io::{self, Write};
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Read, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use crossterm::{
-    event::{self, Event, KeyEvent, KeyEventKind},
-    terminal::size,
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, size, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
 
+// whether the terminal is changed by `Terminal::setup`
+static IS_SETUP: AtomicBool = AtomicBool::new(false);
+
+pub enum TerminalInput {
+    Key(KeyEvent),
+    // text pasted with bracketed paste
+    Paste(String),
+}
+
 pub struct Terminal;
 
 impl Terminal {
     const BACKSPACE: &'static str = "\x1B[K";
+    // OSC 11 asks for the background color, and the primary device
+    // attributes query after it is answered by almost every terminal,
+    // so the reading ends even if OSC 11 is not supported.
+    const BACKGROUND_QUERY: &'static str = "\x1B]11;?\x1B\\\x1B[c";
 
     pub fn width() -> usize {
         size().unwrap().0 as usize
@@ -22,17 +43,129 @@ impl Terminal {
         io::stdout().flush()
     }
 
+    pub fn setup() -> io::Result<()> {
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        enable_raw_mode()?;
+        IS_SETUP.store(true, Ordering::SeqCst);
+        return Ok(());
+    }
+
+    // restores the terminal changed by `setup`, does nothing
+    // if the terminal is not changed or already restored.
+    pub fn restore() -> io::Result<()> {
+        if !IS_SETUP.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        disable_raw_mode()?;
+        execute!(io::stdout(), DisableBracketedPaste, LeaveAlternateScreen)?;
+        return Ok(());
+    }
+
     pub fn clear_after_cursor() -> io::Result<()> {
         print!("{}", Self::BACKSPACE);
         Self::flush()
     }
 
-    pub fn get_key() -> Option<KeyEvent> {
-        if let Ok(Event::Key(key)) = event::read() {
-            if key.kind == KeyEventKind::Press {
-                return Some(key);
+    // returns None if no input in `timeout`.
+    pub fn get_input(timeout: Duration) -> Option<TerminalInput> {
+        if !event::poll(timeout).unwrap_or(false) {
+            return None;
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                Some(TerminalInput::Key(key))
             }
+            Ok(Event::Paste(text)) => Some(TerminalInput::Paste(text)),
+            _ => None,
         }
-        None
     }
+
+    // queries the background color as RGB, must be called before `setup`.
+    // returns None if the terminal does not answer in time.
+    pub fn background_color() -> Option<(u8, u8, u8)> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return None;
+        }
+        let mut tty = File::open("/dev/tty").ok()?;
+
+        enable_raw_mode().ok()?;
+        print!("{}", Self::BACKGROUND_QUERY);
+        let _ = Self::flush();
+        let response = read_query_response(&mut tty, Duration::from_millis(200));
+        let _ = disable_raw_mode();
+        return parse_background_response(&String::from_utf8_lossy(&response?));
+    }
+}
+
+// reads the answers of `BACKGROUND_QUERY` until the answer of device
+// attributes `ESC [ ? ... c`, returns None when `timeout` expires.
+// the tty is polled on current thread, so nothing is left reading
+// keystrokes after returning, and the answered bytes arriving before
+// the timeout are dropped instead of being read as input.
+#[cfg(unix)]
+fn read_query_response(tty: &mut File, timeout: Duration) -> Option<Vec<u8>> {
+    use std::{os::fd::AsRawFd, time::Instant};
+
+    let deadline = Instant::now() + timeout;
+    let mut response = vec![];
+    let mut byte = [0u8];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let mut poll_fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `poll_fd` is a valid array of one `pollfd`.
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 || tty.read(&mut byte).unwrap_or(0) != 1 {
+            // SAFETY: discards the input not read yet of the opened tty.
+            unsafe { libc::tcflush(tty.as_raw_fd(), libc::TCIFLUSH) };
+            return None;
+        }
+        response.push(byte[0]);
+        if byte[0] == b'c' && response.windows(3).any(|w| w == b"\x1B[?") {
+            return Some(response);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn read_query_response(_tty: &mut File, _timeout: Duration) -> Option<Vec<u8>> {
+    return None;
+}
+
+// parses `ESC ] 11 ; rgb:RRRR/GGGG/BBBB`, channels may have 1 to 4 hex digits.
+fn parse_background_response(response: &str) -> Option<(u8, u8, u8)> {
+    let start = response.find("]11;rgb:")? + "]11;rgb:".len();
+    let end = response[start..]
+        .find(['\x07', '\x1B'])
+        .map_or(response.len(), |i| start + i);
+    let channels: Vec<&str> = response[start..end].split('/').collect();
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    let channel = |hex: &str| {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (hex.len() * 4)) - 1;
+        return Some((value * 255 / max) as u8);
+    };
+    return Some((channel(r)?, channel(g)?, channel(b)?));
+}
+
+#[test]
+fn parse_background_response_test() {
+    assert_eq!(
+        parse_background_response("\x1B]11;rgb:ffff/ffff/dddd\x1B\\\x1B[?62;c"),
+        Some((255, 255, 221))
+    );
+    assert_eq!(
+        parse_background_response("\x1B]11;rgb:1e/1e/2e\x07"),
+        Some((30, 30, 46))
+    );
+    assert_eq!(parse_background_response("\x1B[?1;2c"), None);
+    assert_eq!(parse_background_response("\x1B]11;rgb:12/34\x07"), None);
 }